@@ -12,18 +12,55 @@ use x11rb::rust_connection::RustConnection;
 pub mod keybind;
 use keybind::Keybind;
 
-use crate::wm::actions::ActionType;
+use crate::wm::actions::{ActionType, WorkspaceTarget};
+use crate::wm::client::Layer;
+use crate::wm::container::ContainerMode;
+use crate::wm::layout::{Orientation, Split};
+
+// A window kept in the scratchpad, and the command that starts it
+pub struct Scratchpad {
+    pub class: String,
+    pub prog: String,
+    pub args: Vec<String>,
+}
+
+// Settings for the windows of a class
+pub struct Rule {
+    pub class: String,
+    pub size_hints: bool, // Fit tiles to the sizes the window asks for in WM_NORMAL_HINTS
+}
 
 pub struct Config {
     pub connection: RustConnection,
     pub screen_num: usize,
     pub gap: u16,
     pub border: u16,
+    pub tab_height: u16,
+    pub nmaster: usize,
+    pub orientation: Orientation,
+    pub workspaces: Vec<String>,
+    pub auto_back_and_forth: bool,
+    pub scratchpads: Vec<Scratchpad>,
+    pub rules: Vec<Rule>,
+    pub mouse_modkey: u16,
+    pub snap_threshold: u16,
     pub keybindings: Vec<Keybind>,
     pub protocol_atom: Atom,
     pub delete_atom: Atom,
+    pub net_wm_name_atom: Atom,
+    pub utf8_string_atom: Atom,
+    pub wm_state_atom: Atom,
+    pub hidden_state_atom: Atom,
+    pub client_list_atom: Atom,
+    pub window_type_atom: Atom,
+    pub floating_type_atoms: Vec<Atom>,
+    pub net_wm_state_atom: Atom,
+    pub layer_type_atoms: Vec<(Atom, Layer)>,
+    pub layer_state_atoms: Vec<(Atom, Layer)>,
 }
 
+// Building a config connects to the X server and reads the config file, which a Default impl would hide
+#[allow(clippy::new_without_default)]
 impl Config {
     pub fn new() -> Self {
         let (connection, screen_num) = x11rb::connect(None)
@@ -37,6 +74,60 @@ impl Config {
             .expect("Unable to get delete atom")
             .reply()
             .expect("Unable to get delete atom").atom;
+        let net_wm_name_atom = connection.intern_atom(false, b"_NET_WM_NAME")
+            .expect("Unable to get name atom")
+            .reply()
+            .expect("Unable to get name atom").atom;
+        let utf8_string_atom = connection.intern_atom(false, b"UTF8_STRING")
+            .expect("Unable to get string atom")
+            .reply()
+            .expect("Unable to get string atom").atom;
+        let wm_state_atom = connection.intern_atom(false, b"WM_STATE")
+            .expect("Unable to get state atom")
+            .reply()
+            .expect("Unable to get state atom").atom;
+        let hidden_state_atom = connection.intern_atom(false, b"_NET_WM_STATE_HIDDEN")
+            .expect("Unable to get hidden state atom")
+            .reply()
+            .expect("Unable to get hidden state atom").atom;
+        let client_list_atom = connection.intern_atom(false, b"_NET_CLIENT_LIST")
+            .expect("Unable to get client list atom")
+            .reply()
+            .expect("Unable to get client list atom").atom;
+        let window_type_atom = connection.intern_atom(false, b"_NET_WM_WINDOW_TYPE")
+            .expect("Unable to get window type atom")
+            .reply()
+            .expect("Unable to get window type atom").atom;
+        // Window types that float instead of being tiled
+        let floating_type_atoms = [&b"_NET_WM_WINDOW_TYPE_DIALOG"[..], b"_NET_WM_WINDOW_TYPE_UTILITY", b"_NET_WM_WINDOW_TYPE_SPLASH", b"_NET_WM_WINDOW_TYPE_TOOLBAR"]
+            .iter()
+            .map(|name| connection.intern_atom(false, name)
+                .expect("Unable to get window type atom")
+                .reply()
+                .expect("Unable to get window type atom").atom)
+            .collect();
+        let net_wm_state_atom = connection.intern_atom(false, b"_NET_WM_STATE")
+            .expect("Unable to get window state atom")
+            .reply()
+            .expect("Unable to get window state atom").atom;
+        // Window types and states that put windows in a stacking layer of their own
+        let layer_atoms = |names: &[(&[u8], Layer)]| -> Vec<(Atom, Layer)> {
+            names.iter()
+                .map(|(name, layer)| (connection.intern_atom(false, name)
+                    .expect("Unable to get layer atom")
+                    .reply()
+                    .expect("Unable to get layer atom").atom, *layer))
+                .collect()
+        };
+        let layer_type_atoms = layer_atoms(&[
+            (b"_NET_WM_WINDOW_TYPE_DESKTOP", Layer::Desktop),
+            (b"_NET_WM_WINDOW_TYPE_DOCK", Layer::Above),
+            (b"_NET_WM_WINDOW_TYPE_NOTIFICATION", Layer::Notification),
+        ]);
+        let layer_state_atoms = layer_atoms(&[
+            (b"_NET_WM_STATE_BELOW", Layer::Below),
+            (b"_NET_WM_STATE_ABOVE", Layer::Above),
+        ]);
 
         // Get the screen
         let screen = &connection.setup().roots[screen_num];
@@ -53,10 +144,90 @@ impl Config {
             pub keycode: u8,
             pub modkey: u16,
             pub action: String,
+            #[serde(default)]
             pub args: String
         }
 
-        let reads: Vec<JSONRead> = serde_json::from_str(&contents).expect("Misformatted config file.");
+        #[derive(Deserialize)]
+        struct JSONScratchpad {
+            pub class: String,
+            pub command: String
+        }
+
+        #[derive(Deserialize)]
+        struct JSONRule {
+            pub class: String,
+            #[serde(default)]
+            pub size_hints: bool
+        }
+
+        fn default_nmaster() -> usize {1}
+        fn default_orientation() -> String {"left".to_owned()}
+        // Workspaces are given either as a count, which numbers them from 1, or as a list of names
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum JSONWorkspaces {
+            Count(usize),
+            Names(Vec<String>)
+        }
+
+        fn default_workspaces() -> JSONWorkspaces {JSONWorkspaces::Count(9)}
+        fn default_auto_back_and_forth() -> bool {true}
+        fn default_mouse_modkey() -> u16 {8}
+        fn default_snap_threshold() -> u16 {16}
+
+        #[derive(Deserialize)]
+        struct JSONSettings {
+            #[serde(default = "default_nmaster")]
+            pub nmaster: usize,
+            #[serde(default = "default_orientation")]
+            pub orientation: String,
+            #[serde(default = "default_workspaces")]
+            pub workspaces: JSONWorkspaces,
+            #[serde(default = "default_auto_back_and_forth")]
+            pub auto_back_and_forth: bool,
+            #[serde(default)]
+            pub scratchpads: Vec<JSONScratchpad>,
+            #[serde(default)]
+            pub rules: Vec<JSONRule>,
+            #[serde(default = "default_mouse_modkey")]
+            pub mouse_modkey: u16,
+            #[serde(default = "default_snap_threshold")]
+            pub snap_threshold: u16,
+            pub keybindings: Vec<JSONRead>
+        }
+
+        // The config file is either a list of keybindings, or an object holding settings and keybindings
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum JSONFile {
+            Keybindings(Vec<JSONRead>),
+            Settings(JSONSettings)
+        }
+
+        let settings = match serde_json::from_str(&contents).expect("Misformatted config file.") {
+            JSONFile::Keybindings(keybindings) => JSONSettings {nmaster: default_nmaster(), orientation: default_orientation(), workspaces: default_workspaces(), auto_back_and_forth: default_auto_back_and_forth(), scratchpads: Vec::new(), rules: Vec::new(), mouse_modkey: default_mouse_modkey(), snap_threshold: default_snap_threshold(), keybindings},
+            JSONFile::Settings(settings) => settings
+        };
+        let orientation = match settings.orientation.as_str() {
+            "left" => Orientation::Left,
+            "top" => Orientation::Top,
+            "right" => Orientation::Right,
+            "bottom" => Orientation::Bottom,
+            _ => panic!("Misformatted orientation setting.")
+        };
+        let scratchpads = settings.scratchpads.into_iter().map(|scratchpad| {
+            let words = shlex::split(scratchpad.command.as_str()).expect("Unable to get scratchpad command");
+            let prog = words.first().expect("Scratchpad command is empty").to_owned();
+            let args = words[1..].to_owned();
+            Scratchpad {class: scratchpad.class, prog, args}
+        }).collect();
+        let rules = settings.rules.into_iter().map(|rule| Rule {class: rule.class, size_hints: rule.size_hints}).collect();
+        let workspaces = match settings.workspaces {
+            JSONWorkspaces::Count(count) => (1..=count).map(|i| i.to_string()).collect(),
+            JSONWorkspaces::Names(names) => names
+        };
+        let reads = settings.keybindings;
 
         let mut keybindings: Vec<Keybind> = Vec::new();
 
@@ -83,11 +254,122 @@ impl Config {
                         _ => panic!("Misformatted ChangeSize args.")
                     }
                 },
+                "ChangeStackSize" => {
+                    match binding.args.as_str() {
+                        "+" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::ChangeStackSize {amount: 0.25}}),
+                        "-" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::ChangeStackSize {amount: -0.25}}),
+                        _ => panic!("Misformatted ChangeStackSize args.")
+                    }
+                },
                 "PromoteWindow" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::PromoteWindow}),
                 "Close" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::Close}),
                 "NextScreen" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::NextScreen}),
                 "MoveWindow" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::MoveWindow}),
                 "ToggleFull" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::ToggleFull}),
+                "CycleLayout" => {
+                    match binding.args.as_str() {
+                        "+" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::CycleLayout {direction: true}}),
+                        "-" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::CycleLayout {direction: false}}),
+                        _ => panic!("Misformatted CycleLayout args.")
+                    }
+                },
+                "RotateLayout" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::RotateLayout}),
+                "IncMaster" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::IncMaster}),
+                "DecMaster" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::DecMaster}),
+                "SplitDirection" => {
+                    match binding.args.as_str() {
+                        "h" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::SplitDirection {split: Split::Horizontal}}),
+                        "v" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::SplitDirection {split: Split::Vertical}}),
+                        _ => panic!("Misformatted SplitDirection args.")
+                    }
+                },
+                "FlipSplit" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::FlipSplit}),
+                "RotateSplit" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::RotateSplit}),
+                "Group" => {
+                    match binding.args.as_str() {
+                        "tabbed" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::Group {mode: ContainerMode::Tabbed}}),
+                        "stacked" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::Group {mode: ContainerMode::Stacked}}),
+                        _ => panic!("Misformatted Group args.")
+                    }
+                },
+                "Ungroup" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::Ungroup}),
+                "CycleGroup" => {
+                    match binding.args.as_str() {
+                        "+" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::CycleGroup {direction: true}}),
+                        "-" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::CycleGroup {direction: false}}),
+                        _ => panic!("Misformatted CycleGroup args.")
+                    }
+                },
+                "ViewWorkspace" => {
+                    // A plain number is the workspace with that number on the focused monitor
+                    let target = match binding.args.parse::<usize>() {
+                        Ok(number) => WorkspaceTarget::Index(number.checked_sub(1).expect("Workspaces are numbered from 1.")),
+                        Err(_) => parse_target(&binding.args.split_whitespace().collect::<Vec<&str>>(), "ViewWorkspace")
+                    };
+                    keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::ViewWorkspace {target}})
+                },
+                "MoveToWorkspace" => {
+                    let number: usize = binding.args.parse().expect("Misformatted MoveToWorkspace args.");
+                    keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::MoveToWorkspace {index: number.checked_sub(1).expect("Workspaces are numbered from 1.")}})
+                },
+                "ToggleFloat" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::ToggleFloat}),
+                "MoveFloat" | "ResizeFloat" => {
+                    let amounts: Vec<i32> = binding.args.split_whitespace()
+                        .map(|amount| amount.parse().unwrap_or_else(|_| panic!("Misformatted {} args.", binding.action)))
+                        .collect();
+                    let [x, y] = amounts[..] else {
+                        panic!("Misformatted {} args.", binding.action);
+                    };
+                    let action = if binding.action == "MoveFloat" {ActionType::MoveFloat {dx: x, dy: y}} else {ActionType::ResizeFloat {dw: x, dh: y}};
+                    keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action})
+                },
+                "CenterFloat" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::CenterFloat}),
+                "SnapFloat" => {
+                    let edge = match binding.args.as_str() {
+                        "left" => Orientation::Left,
+                        "top" => Orientation::Top,
+                        "right" => Orientation::Right,
+                        "bottom" => Orientation::Bottom,
+                        _ => panic!("Misformatted SnapFloat args.")
+                    };
+                    keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::SnapFloat {edge}})
+                },
+                "Minimize" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::Minimize}),
+                "RestoreLast" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::RestoreLast}),
+                "RestoreAll" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::RestoreAll}),
+                "ToggleAlwaysOnTop" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::ToggleAlwaysOnTop}),
+                "ToggleSticky" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::ToggleSticky}),
+                "MoveToScratchpad" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::MoveToScratchpad}),
+                "ToggleScratchpad" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::ToggleScratchpad {class: binding.args.clone()}}),
+                "CreateWorkspace" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::CreateWorkspace {name: binding.args.clone()}}),
+                "RenameWorkspace" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::RenameWorkspace {name: binding.args.clone()}}),
+                "DeleteWorkspace" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::DeleteWorkspace}),
+                "SwapMonitors" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::SwapMonitors}),
+                "WorkspaceBackAndForth" => keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::WorkspaceBackAndForth}),
+                "SendToWorkspace" => {
+                    let words: Vec<&str> = binding.args.split_whitespace().collect();
+                    let follow = match words.get(2) {
+                        Some(&"follow") => true,
+                        None => false,
+                        _ => panic!("Misformatted SendToWorkspace args.")
+                    };
+                    let target = parse_target(&words, "SendToWorkspace");
+                    keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action: ActionType::SendToWorkspace {target, follow}})
+                },
+                "ViewTag" | "ToggleView" | "TagWindow" | "ToggleTag" => {
+                    let number: u32 = binding.args.parse().expect("Misformatted tag args.");
+                    if !(1..=32).contains(&number) {
+                        panic!("Tags are numbered from 1 to 32.");
+                    }
+                    let tag = number - 1;
+                    let action = match binding.action.as_str() {
+                        "ViewTag" => ActionType::ViewTag {tag},
+                        "ToggleView" => ActionType::ToggleView {tag},
+                        "TagWindow" => ActionType::TagWindow {tag},
+                        _ => ActionType::ToggleTag {tag}
+                    };
+                    keybindings.push(Keybind {keycode: binding.keycode, modkeys: binding.modkey, action})
+                },
                 _ => panic!("Misformatted config file.")
             };
         }
@@ -96,10 +378,45 @@ impl Config {
             connection,
             screen_num,
             gap: 16,
-            border: 2,
+            border: 3,
+            tab_height: 18,
+            nmaster: settings.nmaster,
+            orientation,
+            workspaces,
+            auto_back_and_forth: settings.auto_back_and_forth,
+            scratchpads,
+            rules,
+            mouse_modkey: settings.mouse_modkey,
+            snap_threshold: settings.snap_threshold,
             keybindings,
             protocol_atom,
-            delete_atom
+            delete_atom,
+            net_wm_name_atom,
+            utf8_string_atom,
+            wm_state_atom,
+            hidden_state_atom,
+            client_list_atom,
+            window_type_atom,
+            floating_type_atoms,
+            net_wm_state_atom,
+            layer_type_atoms,
+            layer_state_atoms
         }
     }
 }
+
+// Parse a workspace target given as "workspace <number>", "name <name>" or "monitor <number>"
+fn parse_target(words: &[&str], action: &str) -> WorkspaceTarget {
+    match (words.first(), words.get(1)) {
+        (Some(&"workspace"), Some(number)) => {
+            let number: usize = number.parse().unwrap_or_else(|_| panic!("Misformatted {} args.", action));
+            WorkspaceTarget::Index(number.checked_sub(1).expect("Workspaces are numbered from 1."))
+        },
+        (Some(&"name"), Some(name)) => WorkspaceTarget::Name(name.to_string()),
+        (Some(&"monitor"), Some(number)) => {
+            let number: usize = number.parse().unwrap_or_else(|_| panic!("Misformatted {} args.", action));
+            WorkspaceTarget::Monitor(number.checked_sub(1).expect("Monitors are numbered from 1."))
+        },
+        _ => panic!("Misformatted {} args.", action)
+    }
+}
@@ -3,16 +3,61 @@
 use std::process::*;
 
 use super::*;
+use super::client::{Client, window_geometry};
+use super::container::ContainerMode;
+use super::layout::{LayoutMessage, Orientation, Rect, Split};
+
+// Where to send a window
+pub enum WorkspaceTarget{
+    Index(usize), // Workspace with this position in the config, on whichever monitor holds it
+    Name(String), // Workspace with this name, on whichever monitor holds it
+    Monitor(usize), // Workspace shown on the monitor with this index
+}
 
 pub enum ActionType{
     Run {prog: String, args: Vec<String>},
     Close,
     CycleFocus {direction: bool},
     ChangeSize {amount: i32},
+    ChangeStackSize {amount: f32},
     NextScreen,
     PromoteWindow,
     MoveWindow,
     ToggleFull,
+    CycleLayout {direction: bool},
+    RotateLayout,
+    IncMaster,
+    DecMaster,
+    SplitDirection {split: Split},
+    FlipSplit,
+    RotateSplit,
+    Group {mode: ContainerMode},
+    Ungroup,
+    CycleGroup {direction: bool},
+    ViewWorkspace {target: WorkspaceTarget},
+    MoveToWorkspace {index: usize},
+    SendToWorkspace {target: WorkspaceTarget, follow: bool},
+    WorkspaceBackAndForth,
+    SwapMonitors,
+    CreateWorkspace {name: String},
+    RenameWorkspace {name: String},
+    DeleteWorkspace,
+    MoveToScratchpad,
+    ToggleScratchpad {class: String},
+    ToggleSticky,
+    ToggleAlwaysOnTop,
+    ToggleFloat,
+    MoveFloat {dx: i32, dy: i32},
+    ResizeFloat {dw: i32, dh: i32},
+    CenterFloat,
+    SnapFloat {edge: Orientation},
+    Minimize,
+    RestoreLast,
+    RestoreAll,
+    ViewTag {tag: u32},
+    ToggleView {tag: u32},
+    TagWindow {tag: u32},
+    ToggleTag {tag: u32},
     Quit
 }
 
@@ -24,10 +69,45 @@ impl<'a> WindowManager<'a> {
             ActionType::Close => self.close(),
             ActionType::CycleFocus{direction} => self.cycle_focus(direction),
             ActionType::ChangeSize { amount } => self.change_size(amount),
+            ActionType::ChangeStackSize { amount } => self.change_stack_size(amount),
             ActionType::NextScreen => self.next_screen(),
             ActionType::PromoteWindow => self.promote_window(),
             ActionType::MoveWindow => self.move_window(),
             ActionType::ToggleFull => self.toggle_full(),
+            ActionType::CycleLayout{direction} => self.cycle_layout(direction),
+            ActionType::RotateLayout => self.rotate_layout(),
+            ActionType::IncMaster => self.change_nmaster(1),
+            ActionType::DecMaster => self.change_nmaster(-1),
+            ActionType::SplitDirection{split} => self.layout_message(LayoutMessage::SetSplit(*split)),
+            ActionType::FlipSplit => self.layout_message(LayoutMessage::Flip),
+            ActionType::RotateSplit => self.layout_message(LayoutMessage::Rotate),
+            ActionType::Group{mode} => self.group(mode),
+            ActionType::Ungroup => self.ungroup(),
+            ActionType::CycleGroup{direction} => self.cycle_group(direction),
+            ActionType::ViewWorkspace{target} => self.view_workspace(target),
+            ActionType::MoveToWorkspace{index} => self.move_to_workspace(*index),
+            ActionType::SendToWorkspace{target, follow} => self.send_to_workspace(target, *follow),
+            ActionType::WorkspaceBackAndForth => self.workspace_back_and_forth(),
+            ActionType::SwapMonitors => self.swap_monitors(),
+            ActionType::CreateWorkspace{name} => self.create_workspace(name),
+            ActionType::RenameWorkspace{name} => self.rename_workspace(name),
+            ActionType::DeleteWorkspace => self.delete_workspace(),
+            ActionType::MoveToScratchpad => self.move_to_scratchpad(),
+            ActionType::ToggleScratchpad{class} => self.toggle_scratchpad(class),
+            ActionType::ToggleSticky => self.toggle_sticky(),
+            ActionType::ToggleAlwaysOnTop => self.toggle_always_on_top(),
+            ActionType::ToggleFloat => self.toggle_float(),
+            ActionType::MoveFloat{dx, dy} => self.move_float(*dx, *dy),
+            ActionType::ResizeFloat{dw, dh} => self.resize_float(*dw, *dh),
+            ActionType::CenterFloat => self.center_float(),
+            ActionType::SnapFloat{edge} => self.snap_float(*edge),
+            ActionType::Minimize => self.minimize(),
+            ActionType::RestoreLast => self.restore_last(),
+            ActionType::RestoreAll => self.restore_all(),
+            ActionType::ViewTag{tag} => self.view_tag(*tag),
+            ActionType::ToggleView{tag} => self.toggle_view(*tag),
+            ActionType::TagWindow{tag} => self.tag_window(*tag),
+            ActionType::ToggleTag{tag} => self.toggle_tag(*tag),
             ActionType::Quit => self.quit()
         }
     }
@@ -39,13 +119,13 @@ impl<'a> WindowManager<'a> {
 
     // Close the focused window
     pub fn close(&mut self){
-        self.workspaces[self.focused].close_focused();
+        self.workspace().close_focused();
     }
 
 
     // Cycle focus through windows
     pub fn cycle_focus(&mut self, direction: &bool){
-        let ws = &mut self.workspaces[self.focused];
+        let ws = self.workspace();
         if ws.windows.is_empty(){
             return;
         }
@@ -56,33 +136,385 @@ impl<'a> WindowManager<'a> {
     }
 
     pub fn next_screen(&mut self){
-        self.workspaces[self.focused].set_active(false);
-        self.focused = (self.focused+1) % self.workspaces.len();
-        self.workspaces[self.focused].set_active(true);
+        self.workspace().set_active(false);
+        self.focused = (self.focused+1) % self.monitors.len();
+        self.workspace().set_active(true);
     }
 
     pub fn change_size(&mut self, amount: &i32){
-        let ws = &mut self.workspaces[self.focused];
+        let ws = self.workspace();
         ws.set_master_width((ws.master_width as i32 + amount) as u16);
     }
 
+    pub fn change_stack_size(&mut self, amount: &f32){
+        self.workspace().change_stack_size(*amount);
+    }
+
     pub fn promote_window(&mut self){
-        self.workspaces[self.focused].promote_focused();
+        self.workspace().promote_focused();
     }
 
     pub fn move_window(&mut self){
-        if self.workspaces.len() <= 1 || self.workspaces[self.focused].windows.is_empty(){
+        let next = (self.focused+1) % self.monitors.len();
+        self.send_to_workspace(&WorkspaceTarget::Monitor(next), true);
+    }
+
+    pub fn toggle_full(&mut self){
+        self.workspace().toggle_full();
+    }
+
+    pub fn cycle_layout(&mut self, direction: &bool){
+        self.workspace().cycle_layout(*direction);
+    }
+
+    pub fn rotate_layout(&mut self){
+        self.workspace().rotate_layout();
+    }
+
+    pub fn change_nmaster(&mut self, amount: i32){
+        self.workspace().change_nmaster(amount);
+    }
+
+    pub fn layout_message(&mut self, message: LayoutMessage){
+        self.workspace().layout_message(message);
+    }
+
+    pub fn group(&mut self, mode: &ContainerMode){
+        self.workspace().group_focused(*mode);
+    }
+
+    pub fn ungroup(&mut self){
+        self.workspace().ungroup_focused();
+    }
+
+    pub fn cycle_group(&mut self, direction: &bool){
+        self.workspace().cycle_group(*direction);
+    }
+
+    // Show a workspace on the focused monitor. A workspace shown on another monitor swaps places
+    // with the focused one, and a hidden workspace of another monitor is pulled over.
+    pub fn view_workspace(&mut self, target: &WorkspaceTarget){
+        let Some((mon, index)) = self.resolve(target) else {
+            return;
+        };
+        if mon == self.focused {
+            if index == self.monitors[mon].current && self.config.auto_back_and_forth {
+                self.workspace_back_and_forth();
+                return;
+            }
+            self.show_workspace(mon, index);
+        } else if index == self.monitors[mon].current {
+            self.swap_shown(self.focused, mon);
+        } else if let Some(ws) = self.monitors[mon].take(index) {
+            let index = self.monitors[self.focused].adopt(ws);
+            self.show_workspace(self.focused, index);
+        }
+    }
+
+    // Exchange the workspaces of the focused monitor and the next one
+    pub fn swap_monitors(&mut self){
+        let next = (self.focused+1) % self.monitors.len();
+        self.swap_shown(self.focused, next);
+    }
+
+    // Exchange the workspaces shown on two monitors, each keeping its layout and master width.
+    // Sticky windows stay on their own monitor.
+    pub fn swap_shown(&mut self, a: usize, b: usize){
+        if a == b {
             return;
         }
-        let win_opt = self.workspaces[self.focused].remove_focused();
-        if let Some(win) = win_opt {
-            self.next_screen();
-            self.workspaces[self.focused].add_window(win);
+        let sticky = [self.monitors[a].workspace_mut().take_sticky(), self.monitors[b].workspace_mut().take_sticky()];
+        let (low, high) = (a.min(b), a.max(b));
+        let (left, right) = self.monitors.split_at_mut(high);
+        std::mem::swap(left[low].workspace_mut(), right[0].workspace_mut());
+        for (i, sticky) in [a, b].into_iter().zip(sticky) {
+            let mon = &mut self.monitors[i];
+            let (x, y, width, height) = (mon.x, mon.y, mon.width, mon.height);
+            mon.workspace_mut().set_geometry(x, y, width, height);
+            for client in sticky {
+                mon.workspace_mut().adopt_client(client);
+            }
+            mon.workspace_mut().set_active(i == self.focused);
         }
     }
 
-    pub fn toggle_full(&mut self){
-        self.workspaces[self.focused].toggle_full();
+    // Go back to the workspace the focused monitor showed before the current one
+    pub fn workspace_back_and_forth(&mut self){
+        if let Some(previous) = self.previous_workspace(self.focused) {
+            self.show_workspace(self.focused, previous);
+        }
+    }
+
+    // Index of the most recently viewed workspace of a monitor that still exists
+    pub fn previous_workspace(&self, mon: usize) -> Option<usize> {
+        self.history[mon].iter().rev().find_map(|id| self.monitors[mon].find_id(*id))
+    }
+
+    // Show a workspace on a monitor, remembering the one it replaces
+    pub fn show_workspace(&mut self, mon: usize, index: usize){
+        let Some(target) = self.monitors[mon].workspaces.get(index).map(|ws| ws.id) else {
+            return;
+        };
+        let previous = self.monitors[mon].workspace().id;
+        if self.monitors[mon].view(index) {
+            let monitor = &self.monitors[mon];
+            let history = &mut self.history[mon];
+            history.retain(|id| *id != previous && *id != target && monitor.find_id(*id).is_some());
+            if monitor.find_id(previous).is_some() {
+                history.push(previous);
+            }
+        }
+    }
+
+    // Show the workspace with the given name on the focused monitor, creating it if there is none
+    pub fn create_workspace(&mut self, name: &str){
+        let target = WorkspaceTarget::Name(name.to_owned());
+        if let Some((mon, index)) = self.resolve(&target) {
+            if mon != self.focused || index != self.monitors[mon].current {
+                self.view_workspace(&target);
+            }
+            return;
+        }
+        let id = self.next_workspace_id;
+        self.next_workspace_id += 1;
+        let index = self.monitors[self.focused].create(id, name.to_owned(), self.config);
+        self.show_workspace(self.focused, index);
+    }
+
+    // Give the focused workspace a new name, unless another workspace has it
+    pub fn rename_workspace(&mut self, name: &str){
+        if self.resolve(&WorkspaceTarget::Name(name.to_owned())).is_none() {
+            self.workspace().name = name.to_owned();
+        }
+    }
+
+    // Remove the focused workspace, moving its windows to the workspace shown instead
+    pub fn delete_workspace(&mut self){
+        let mon = self.focused;
+        if self.monitors[mon].workspaces.len() <= 1 {
+            return;
+        }
+        let current = self.monitors[mon].current;
+        let target = self.previous_workspace(mon).unwrap_or(if current == 0 {1} else {current - 1});
+        let id = self.workspace().id;
+        let clients = self.workspace().take_clients();
+        self.show_workspace(mon, target);
+        if let Some(index) = self.monitors[mon].find_id(id) {
+            self.monitors[mon].remove(index);
+        }
+        self.history[mon].retain(|previous| *previous != id);
+        for client in clients {
+            self.workspace().add_client(client);
+        }
+    }
+
+    pub fn move_to_workspace(&mut self, index: usize){
+        self.send_to_workspace(&WorkspaceTarget::Index(index), false);
+    }
+
+    // Find the monitor and workspace index a target refers to
+    pub fn resolve(&self, target: &WorkspaceTarget) -> Option<(usize, usize)> {
+        match target {
+            WorkspaceTarget::Index(index) => self.monitors.iter().enumerate().find_map(|(i, mon)| mon.find_id(*index).map(|index| (i, index))),
+            WorkspaceTarget::Name(name) => self.monitors.iter().enumerate().find_map(|(i, mon)| mon.find(name).map(|index| (i, index))),
+            WorkspaceTarget::Monitor(mon) => self.monitors.get(*mon).map(|monitor| (*mon, monitor.current))
+        }
+    }
+
+    // Move the focused window to another workspace, optionally taking focus along with it
+    pub fn send_to_workspace(&mut self, target: &WorkspaceTarget, follow: bool){
+        let Some((mon, index)) = self.resolve(target) else {
+            return;
+        };
+        if mon == self.focused && index == self.monitors[mon].current {
+            return;
+        }
+        let full = self.workspace().is_full;
+        let mut clients = self.workspace().take_focused();
+        let Some(win) = clients.first().map(|client| client.window) else {
+            return;
+        };
+        // A window leaving a fullscreen workspace stays fullscreen on its own, without changing the
+        // mode of the workspace it lands on
+        clients[0].fullscreen |= full;
+        let (dx, dy) = (self.monitors[mon].x as i32 - self.monitors[self.focused].x as i32, self.monitors[mon].y as i32 - self.monitors[self.focused].y as i32);
+        let dest = &mut self.monitors[mon].workspaces[index];
+        // Dialogs go along with their window, keeping their place on the monitor
+        for mut client in clients {
+            client.geometry = Rect::new(client.geometry.x + dx, client.geometry.y + dy, client.geometry.width, client.geometry.height);
+            dest.add_client(client);
+        }
+        if follow {
+            self.focus_workspace(mon, index);
+            self.workspace().focus_window_id(win);
+        }
+    }
+
+    // Show a workspace and move focus to its monitor
+    pub fn focus_workspace(&mut self, mon: usize, index: usize){
+        if mon != self.focused {
+            self.workspace().set_active(false);
+            self.focused = mon;
+        }
+        self.show_workspace(mon, index);
+        self.workspace().set_active(true);
+    }
+
+    // Show only the given tag on the focused monitor
+    pub fn view_tag(&mut self, tag: u32){
+        self.workspace().set_view(1 << tag);
+    }
+
+    // Add or remove the given tag from what the focused monitor shows
+    pub fn toggle_view(&mut self, tag: u32){
+        let ws = self.workspace();
+        ws.set_view(ws.view ^ (1 << tag));
+    }
+
+    // Put the focused window on the given tag only
+    pub fn tag_window(&mut self, tag: u32){
+        self.workspace().set_focused_tags(1 << tag);
+    }
+
+    // Add or remove the focused window from the given tag
+    pub fn toggle_tag(&mut self, tag: u32){
+        let ws = self.workspace();
+        ws.set_focused_tags(ws.focused_tags() ^ (1 << tag));
+    }
+
+    // Keep the focused window on screen whichever workspace its monitor shows
+    pub fn toggle_sticky(&mut self){
+        self.workspace().toggle_sticky();
+    }
+
+    pub fn toggle_always_on_top(&mut self){
+        self.workspace().toggle_always_on_top();
+    }
+
+    pub fn toggle_float(&mut self){
+        self.workspace().toggle_float();
+    }
+
+    pub fn move_float(&mut self, dx: i32, dy: i32){
+        self.workspace().move_focused_float(dx, dy);
+    }
+
+    pub fn resize_float(&mut self, dw: i32, dh: i32){
+        self.workspace().resize_focused_float(dw, dh);
+    }
+
+    pub fn center_float(&mut self){
+        self.workspace().center_focused_float();
+    }
+
+    pub fn snap_float(&mut self, edge: Orientation){
+        self.workspace().snap_focused_float(edge);
+    }
+
+    pub fn minimize(&mut self){
+        self.workspace().minimize_focused();
+        self.publish_clients();
+    }
+
+    pub fn restore_last(&mut self){
+        self.workspace().restore_last();
+        self.publish_clients();
+    }
+
+    pub fn restore_all(&mut self){
+        while self.workspace().restore_last() {}
+        self.publish_clients();
+    }
+
+    // Hide the focused window in the scratchpad
+    pub fn move_to_scratchpad(&mut self){
+        // Dialogs are kept along with their window, to come back when it does
+        let mut clients = self.workspace().take_focused();
+        if let Some(client) = clients.first_mut() {
+            client.scratchpad = true;
+        }
+        for mut client in clients {
+            client.hide(self.config);
+            self.scratchpad.push(client);
+        }
+    }
+
+    // Show the scratchpad window with the given class on the focused monitor, hide it again if it
+    // is already there, or start it if it isn't running
+    pub fn toggle_scratchpad(&mut self, class: &String){
+        let ws = self.workspace();
+        let shown = ws.windows.iter().copied().find(|win| ws.clients.get(win).is_some_and(|client| client.scratchpad && client.class == *class));
+        if let Some(client) = shown.and_then(|win| ws.remove_window(win)) {
+            let dialogs = ws.take_transients(client.window);
+            for mut client in std::iter::once(client).chain(dialogs) {
+                client.hide(self.config);
+                self.scratchpad.push(client);
+            }
+            return;
+        }
+
+        let elsewhere = self.monitors.iter().flat_map(|mon| mon.workspaces.iter())
+            .flat_map(|ws| ws.clients.values())
+            .find(|client| client.scratchpad && client.class == *class)
+            .map(|client| client.window);
+        let client = match elsewhere {
+            Some(win) => self.monitors.iter_mut().flat_map(|mon| mon.workspaces.iter_mut())
+                .find_map(|ws| ws.remove_window(win).map(|client| (client, ws.take_transients(win)))),
+            None => self.scratchpad.iter().position(|client| client.scratchpad && client.class == *class).map(|pos| {
+                let client = self.scratchpad.remove(pos);
+                let (dialogs, rest) = std::mem::take(&mut self.scratchpad).into_iter().partition(|dialog| dialog.transient_for == Some(client.window));
+                self.scratchpad = rest;
+                (client, dialogs)
+            })
+        };
+        match client {
+            Some((client, dialogs)) => self.show_scratchpad(client, dialogs),
+            None => {
+                if let Some(scratchpad) = self.config.scratchpads.iter().find(|scratchpad| scratchpad.class == *class) {
+                    self.run(&scratchpad.prog, &scratchpad.args);
+                }
+            }
+        }
+    }
+
+    // Float a scratchpad window in the middle of the focused monitor, with its dialogs over it
+    pub fn show_scratchpad(&mut self, mut client: Client, dialogs: Vec<Client>){
+        let ws = self.workspace();
+        client.floating = true;
+        client.geometry = ws.centered(ws.width as i32/2, ws.height as i32/2);
+        let area = client.geometry;
+        ws.add_client(client);
+        for mut dialog in dialogs {
+            dialog.geometry = ws.centered_over(area, dialog.geometry.width, dialog.geometry.height);
+            ws.add_client(dialog);
+        }
+    }
+
+    // Float a dialog centered over the window it belongs to, on that window's workspace
+    pub fn show_dialog(&mut self, mut client: Client){
+        let size = window_geometry(self.config, client.window).unwrap_or(Rect::new(0, 0, 1, 1));
+        let parent = client.transient_for.and_then(|parent| {
+            self.monitors.iter().enumerate()
+                .flat_map(|(mon, monitor)| monitor.workspaces.iter().enumerate().map(move |(index, ws)| (mon, index, ws)))
+                .find(|(_, _, ws)| ws.clients.contains_key(&parent))
+                .map(|(mon, index, ws)| {
+                    let area = if ws.clients[&parent].floating {ws.clients[&parent].geometry} else {window_geometry(self.config, parent).unwrap_or(size)};
+                    (mon, index, area)
+                })
+        });
+        client.floating = true;
+        match parent {
+            Some((mon, index, area)) => {
+                let ws = &mut self.monitors[mon].workspaces[index];
+                client.geometry = ws.centered_over(area, size.width, size.height);
+                ws.add_client(client);
+            },
+            None => {
+                let ws = self.workspace();
+                client.geometry = ws.centered(size.width, size.height);
+                ws.add_client(client);
+            }
+        }
     }
 
     // Exit the window manager
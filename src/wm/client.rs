@@ -0,0 +1,242 @@
+use x11rb::properties::{WmClass, WmSizeHints};
+use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt, PropMode};
+use x11rb::wrapper::ConnectionExt as _;
+
+use crate::config::Config;
+
+use super::layout::Rect;
+
+// Values of the ICCCM WM_STATE property
+pub const NORMAL_STATE: u32 = 1;
+pub const ICONIC_STATE: u32 = 3;
+
+// Stacking layers, from the bottom of the screen up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    Desktop,
+    Below,
+    Tiled,
+    Floating,
+    Above,
+    Fullscreen,
+    Notification,
+}
+
+// State the window manager keeps about a single window
+#[derive(Debug, Clone)]
+pub struct Client {
+    pub window: u32, // Window id
+    pub class: String, // Class from WM_CLASS
+    pub weight: f32, // Share of its column, relative to the other windows there
+    pub tags: u32, // Bitmask of the tags the window is on
+    pub mapped: bool, // Is the window currently mapped
+    pub pending_unmaps: usize, // Unmaps done by the window manager that have not been reported back yet
+    pub floating: bool, // Is the window left out of the layout
+    pub fullscreen: bool, // Does the window cover its monitor on its own, whatever the workspace's mode
+    pub geometry: Rect, // Position and size of the window while floating
+    pub scratchpad: bool, // Does the window go back to the scratchpad when toggled
+    pub sticky: bool, // Does the window follow its monitor from workspace to workspace
+    pub transient_for: Option<u32>, // Window this one is a dialog of
+    pub hints: SizeHints, // Size constraints from WM_NORMAL_HINTS
+    pub honor_hints: bool, // Are tiles fitted to the size constraints
+    pub layer: Option<Layer>, // Layer the window is kept in, instead of the one its state gives it
+    pub requested_layer: Option<Layer>, // Layer the window asked for through _NET_WM_STATE
+}
+
+impl Client {
+    pub fn new(window: u32) -> Self {
+        Self {
+            window,
+            class: String::new(),
+            weight: 1.0,
+            tags: 0,
+            mapped: true,
+            pending_unmaps: 0,
+            floating: false,
+            fullscreen: false,
+            geometry: Rect::new(0, 0, 1, 1),
+            scratchpad: false,
+            sticky: false,
+            transient_for: None,
+            hints: SizeHints::default(),
+            honor_hints: false,
+            layer: None,
+            requested_layer: None,
+        }
+    }
+
+    pub fn show(&mut self, config: &Config) {
+        if !self.mapped {
+            config.connection.map_window(self.window).expect("Unable to map window");
+            self.mapped = true;
+        }
+    }
+
+    // Unmap the window, remembering to ignore the UnmapNotify it causes so the window stays managed
+    pub fn hide(&mut self, config: &Config) {
+        if self.mapped {
+            config.connection.unmap_window(self.window).expect("Unable to unmap window");
+            self.mapped = false;
+            self.pending_unmaps += 1;
+        }
+    }
+
+    // Set the ICCCM WM_STATE of the window, telling clients and pagers whether it is iconified,
+    // and mark iconified windows with _NET_WM_STATE_HIDDEN for status bars to list
+    pub fn set_state(&self, config: &Config, state: u32) {
+        config.connection.change_property32(PropMode::REPLACE, self.window, config.wm_state_atom, config.wm_state_atom, &[state, 0])
+            .expect("Unable to set window state");
+        let mut states = window_atoms(config, self.window, config.net_wm_state_atom);
+        states.retain(|atom| *atom != config.hidden_state_atom);
+        if state == ICONIC_STATE {
+            states.push(config.hidden_state_atom);
+        }
+        config.connection.change_property32(PropMode::REPLACE, self.window, config.net_wm_state_atom, AtomEnum::ATOM, &states)
+            .expect("Unable to set window state");
+    }
+
+    // Show the layer the window is kept in through _NET_WM_STATE_ABOVE and _NET_WM_STATE_BELOW
+    pub fn set_layer_state(&self, config: &Config) {
+        let mut states = window_atoms(config, self.window, config.net_wm_state_atom);
+        states.retain(|atom| config.layer_state_atoms.iter().all(|(layer_atom, _)| layer_atom != atom));
+        states.extend(config.layer_state_atoms.iter().filter(|(_, layer)| self.layer == Some(*layer)).map(|(atom, _)| *atom));
+        config.connection.change_property32(PropMode::REPLACE, self.window, config.net_wm_state_atom, AtomEnum::ATOM, &states)
+            .expect("Unable to set window state");
+    }
+}
+
+// Size constraints a window asks for in WM_NORMAL_HINTS
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SizeHints {
+    pub min: Option<(i32, i32)>, // Smallest size the window can use
+    pub max: Option<(i32, i32)>, // Largest size the window can use
+    pub base: Option<(i32, i32)>, // Size the increments are counted from
+    pub increment: Option<(i32, i32)>, // Steps the size grows in, such as a terminal's character cell
+}
+
+impl SizeHints {
+    // Can the window only be one size
+    pub fn is_fixed(&self) -> bool {
+        self.min.is_some() && self.min == self.max
+    }
+
+    // Fit a size to the hints, rounding down to the increments
+    pub fn constrain(&self, width: i32, height: i32) -> (i32, i32) {
+        // ICCCM lets the minimum and base sizes stand in for each other
+        let min = self.min.or(self.base).unwrap_or((1, 1));
+        let base = self.base.or(self.min).unwrap_or((0, 0));
+        let (mut width, mut height) = (width, height);
+        if let Some((inc_width, inc_height)) = self.increment {
+            if inc_width > 1 {
+                width = base.0 + (width - base.0) / inc_width * inc_width;
+            }
+            if inc_height > 1 {
+                height = base.1 + (height - base.1) / inc_height * inc_height;
+            }
+        }
+        // Rounding from a base below the minimum can land under it
+        (width, height) = (width.max(min.0), height.max(min.1));
+        if let Some((max_width, max_height)) = self.max.filter(|max| max.0 > 0 && max.1 > 0) {
+            width = width.min(max_width);
+            height = height.min(max_height);
+        }
+        (width.max(1), height.max(1))
+    }
+}
+
+// Read the size constraints of a window from WM_NORMAL_HINTS
+pub fn window_size_hints(config: &Config, win: u32) -> SizeHints {
+    WmSizeHints::get_normal_hints(&config.connection, win).ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .map(|hints| SizeHints {
+            min: hints.min_size,
+            max: hints.max_size,
+            base: hints.base_size,
+            increment: hints.size_increment,
+        })
+        .unwrap_or_default()
+}
+
+// Get the position and size of a window, not counting its border
+pub fn window_geometry(config: &Config, win: u32) -> Option<Rect> {
+    config.connection.get_geometry(win).ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .map(|geometry| Rect::new(geometry.x as i32, geometry.y as i32, geometry.width as i32, geometry.height as i32))
+}
+
+// Get the window a dialog belongs to from WM_TRANSIENT_FOR
+pub fn window_transient_for(config: &Config, win: u32) -> Option<u32> {
+    config.connection.get_property(false, win, AtomEnum::WM_TRANSIENT_FOR, AtomEnum::WINDOW, 0, 1).ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .and_then(|reply| reply.value32().and_then(|mut values| values.next()))
+        .filter(|parent| *parent != x11rb::NONE && *parent != win)
+}
+
+// Get a list of atoms from a property of a window, such as the kinds of window it says it is
+// from _NET_WM_WINDOW_TYPE
+pub fn window_atoms(config: &Config, win: u32, property: Atom) -> Vec<Atom> {
+    config.connection.get_property(false, win, property, AtomEnum::ATOM, 0, 32).ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .and_then(|reply| reply.value32().map(|values| values.collect()))
+        .unwrap_or_default()
+}
+
+// Get the class of a window from WM_CLASS
+pub fn window_class(config: &Config, win: u32) -> String {
+    WmClass::get(&config.connection, win).ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .map(|class| String::from_utf8_lossy(class.class()).into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_hints_keep_the_size() {
+        assert_eq!(SizeHints::default().constrain(640, 480), (640, 480));
+        assert_eq!(SizeHints::default().constrain(0, -5), (1, 1));
+    }
+
+    #[test]
+    fn sizes_stay_between_min_and_max() {
+        let hints = SizeHints {min: Some((100, 50)), max: Some((800, 600)), ..SizeHints::default()};
+        assert_eq!(hints.constrain(10, 10), (100, 50));
+        assert_eq!(hints.constrain(1000, 1000), (800, 600));
+        assert_eq!(hints.constrain(400, 300), (400, 300));
+    }
+
+    #[test]
+    fn zero_max_is_no_limit() {
+        let hints = SizeHints {max: Some((0, 0)), ..SizeHints::default()};
+        assert_eq!(hints.constrain(5000, 5000), (5000, 5000));
+    }
+
+    #[test]
+    fn sizes_round_down_to_increments_from_base() {
+        let hints = SizeHints {base: Some((4, 2)), increment: Some((8, 16)), ..SizeHints::default()};
+        assert_eq!(hints.constrain(100, 100), (100, 98));
+        assert_eq!(hints.constrain(107, 113), (100, 98));
+    }
+
+    #[test]
+    fn min_stands_in_for_base() {
+        let hints = SizeHints {min: Some((10, 10)), increment: Some((7, 7)), ..SizeHints::default()};
+        assert_eq!(hints.constrain(30, 30), (24, 24));
+    }
+
+    #[test]
+    fn rounding_never_goes_under_min() {
+        let hints = SizeHints {min: Some((100, 100)), base: Some((10, 10)), increment: Some((7, 7)), ..SizeHints::default()};
+        let (width, height) = hints.constrain(100, 101);
+        assert!(width >= 100 && height >= 100);
+    }
+
+    #[test]
+    fn fixed_size() {
+        assert!(SizeHints {min: Some((300, 200)), max: Some((300, 200)), ..SizeHints::default()}.is_fixed());
+        assert!(!SizeHints {min: Some((300, 200)), ..SizeHints::default()}.is_fixed());
+        assert!(!SizeHints::default().is_fixed());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::config::Config;
+
+use super::layout::Rect;
+use super::tabbar::TabBar;
+
+// How a container shows the titles of its windows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerMode {
+    Tabbed, // One row of tabs side by side
+    Stacked, // One title bar per window, on top of each other
+}
+
+// A group of windows sharing a single tile, showing one window at a time
+pub struct Container {
+    pub windows: Vec<u32>, // Windows in the group on the tags being viewed, the first one stands in for the group when tiling
+    pub hidden: Vec<u32>, // Windows in the group on other tags
+    pub active: usize, // Index of the window being shown
+    pub mode: ContainerMode, // How the titles are drawn
+    pub bar: TabBar, // Title bars of the windows
+}
+
+impl Container {
+    pub fn new(config: &Config, windows: Vec<u32>, active: usize, mode: ContainerMode) -> Self {
+        Self {
+            windows,
+            hidden: Vec::new(),
+            active,
+            mode,
+            bar: TabBar::new(config),
+        }
+    }
+
+    pub fn active_window(&self) -> u32 {
+        self.windows[self.active]
+    }
+
+    pub fn contains(&self, win: u32) -> bool {
+        self.windows.contains(&win) || self.hidden.contains(&win)
+    }
+
+    // Move the windows of the group between the shown and hidden lists, keeping the same window active
+    // if it is still shown
+    pub fn apply_view(&mut self, shown: &[u32]) {
+        let active = self.windows.get(self.active).copied();
+        let members: Vec<u32> = self.windows.drain(..).chain(self.hidden.drain(..)).collect();
+        (self.windows, self.hidden, self.active) = partition_view(members, active, shown);
+    }
+
+    // Split the window rectangle of the tile into the title bars, which also cover the border,
+    // and the rectangle left for the windows
+    pub fn split(&self, rect: Rect, border: u16, tab_height: u16) -> (Rect, Rect) {
+        let rows = match self.mode {
+            ContainerMode::Tabbed => 1,
+            ContainerMode::Stacked => self.windows.len() as i32,
+        };
+        split_tile(rect, rows, border, tab_height)
+    }
+
+    pub fn draw(&self, config: &Config) {
+        if self.windows.is_empty() {
+            return;
+        }
+        self.bar.draw(config, &self.windows, Some(self.active_window()), self.mode == ContainerMode::Stacked);
+    }
+}
+
+// Split the members of a group into the shown and hidden windows, and find where the active window
+// ended up among the shown ones, falling back to the first
+fn partition_view(members: Vec<u32>, active: Option<u32>, shown: &[u32]) -> (Vec<u32>, Vec<u32>, usize) {
+    let (windows, hidden): (Vec<u32>, Vec<u32>) = members.into_iter().partition(|win| shown.contains(win));
+    let active = active.and_then(|active| windows.iter().position(|win| *win == active)).unwrap_or(0);
+    (windows, hidden, active)
+}
+
+// Take the given number of title bar rows off the top of a window rectangle, always leaving at
+// least a pixel for the windows
+fn split_tile(rect: Rect, rows: i32, border: u16, tab_height: u16) -> (Rect, Rect) {
+    let bar_height = (rows * tab_height as i32).min(rect.height - 1);
+    let border = border as i32;
+    let bar = Rect::new(rect.x - border, rect.y - border, rect.width + border*2, bar_height);
+    let content = Rect::new(rect.x, rect.y + bar_height, rect.width, rect.height - bar_height);
+    (bar, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bar_covers_the_border_above_the_windows() {
+        let (bar, content) = split_tile(Rect::new(100, 50, 400, 300), 1, 2, 18);
+        assert_eq!(bar, Rect::new(98, 48, 404, 18));
+        assert_eq!(content, Rect::new(100, 68, 400, 282));
+    }
+
+    #[test]
+    fn stacked_rows_never_take_the_whole_tile() {
+        let (bar, content) = split_tile(Rect::new(0, 0, 400, 50), 4, 2, 18);
+        assert_eq!(bar.height, 49);
+        assert_eq!(content, Rect::new(0, 49, 400, 1));
+    }
+
+    #[test]
+    fn view_keeps_the_active_window_when_it_stays_shown() {
+        let (windows, hidden, active) = partition_view(vec![1, 2, 3, 4], Some(3), &[2, 3, 9]);
+        assert_eq!(windows, vec![2, 3]);
+        assert_eq!(hidden, vec![1, 4]);
+        assert_eq!(active, 1);
+    }
+
+    #[test]
+    fn view_falls_back_to_the_first_window_when_the_active_one_is_hidden() {
+        let (windows, hidden, active) = partition_view(vec![1, 2, 3], Some(1), &[2, 3]);
+        assert_eq!(windows, vec![2, 3]);
+        assert_eq!(hidden, vec![1]);
+        assert_eq!(active, 0);
+    }
+}
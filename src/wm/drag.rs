@@ -0,0 +1,189 @@
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{ButtonIndex, ConnectionExt, EventMask, GrabMode, ModMask};
+
+use super::WindowManager;
+use super::client::{SizeHints, window_geometry, window_size_hints};
+use super::layout::Rect;
+use super::workspace::Workspace;
+
+// What dragging the pointer does to the window
+#[derive(Debug, Clone, Copy)]
+pub enum DragKind {
+    Move,
+    Resize {right: bool, bottom: bool, hints: SizeHints}, // Resize from the corner on the given sides
+}
+
+// A window being moved or resized with the mouse
+#[derive(Debug, Clone, Copy)]
+pub struct Drag {
+    pub window: u32, // Window being dragged
+    pub kind: DragKind, // Whether the window is moved or resized
+    pub pointer: (i16, i16), // Pointer position on the root window when the drag started
+    pub geometry: Rect, // Geometry of the window when the drag started
+}
+
+impl Drag {
+    // Geometry of the window with the pointer at the given position, before size hints apply
+    fn geometry_at(&self, x: i16, y: i16) -> Rect {
+        let (dx, dy) = (x as i32 - self.pointer.0 as i32, y as i32 - self.pointer.1 as i32);
+        let start = self.geometry;
+        match self.kind {
+            DragKind::Move => Rect::new(start.x + dx, start.y + dy, start.width, start.height),
+            DragKind::Resize {right, bottom, ..} => Rect::new(
+                if right {start.x} else {start.x + dx},
+                if bottom {start.y} else {start.y + dy},
+                if right {start.width + dx} else {start.width - dx},
+                if bottom {start.height + dy} else {start.height - dy},
+            )
+        }
+    }
+
+    // Fit a resized geometry to the size hints, keeping the corner opposite the dragged one in place
+    fn constrain(&self, rect: Rect) -> Rect {
+        let DragKind::Resize {right, bottom, hints} = self.kind else {
+            return rect;
+        };
+        let (width, height) = hints.constrain(rect.width, rect.height);
+        Rect::new(
+            if right {rect.x} else {rect.x + rect.width - width},
+            if bottom {rect.y} else {rect.y + rect.height - height},
+            width,
+            height,
+        )
+    }
+}
+
+impl<'a> WindowManager<'a> {
+    // Grab the mouse buttons that start a drag anywhere on the screen
+    pub fn grab_drag_buttons(&self) {
+        let root = self.config.connection.setup().roots[self.config.screen_num].root;
+        for button in [ButtonIndex::M1, ButtonIndex::M3] {
+            self.config.connection.grab_button(
+                false,
+                root,
+                EventMask::BUTTON_PRESS | EventMask::BUTTON_RELEASE | EventMask::POINTER_MOTION,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+                x11rb::NONE,
+                x11rb::NONE,
+                button,
+                ModMask::from(self.config.mouse_modkey)
+            ).expect("Unable to grab button");
+        }
+    }
+
+    // Start moving or resizing a window with the mouse, floating it first if it is tiled
+    pub fn start_drag(&mut self, win: u32, resize: bool, x: i16, y: i16) {
+        self.focus_window_id(win);
+        let Some(ws) = self.shown_workspace_of(win) else {
+            return;
+        };
+        let geometry = if ws.clients[&win].floating {
+            ws.clients[&win].geometry
+        } else {
+            let Some(geometry) = window_geometry(ws.config, win) else {
+                return;
+            };
+            ws.float_window(win, geometry);
+            geometry
+        };
+        if !resize {
+            self.drag = Some(Drag {window: win, kind: DragKind::Move, pointer: (x, y), geometry});
+            return;
+        }
+
+        // Resize from the corner nearest the pointer, moving the pointer onto it
+        let right = x as i32 >= geometry.x + geometry.width/2;
+        let bottom = y as i32 >= geometry.y + geometry.height/2;
+        let corner = (if right {geometry.width} else {0}, if bottom {geometry.height} else {0});
+        self.config.connection.warp_pointer(x11rb::NONE, win, 0, 0, 0, 0, corner.0 as i16, corner.1 as i16).expect("Unable to warp pointer");
+        let hints = window_size_hints(self.config, win);
+        self.drag = Some(Drag {
+            window: win,
+            kind: DragKind::Resize {right, bottom, hints},
+            pointer: ((geometry.x + corner.0) as i16, (geometry.y + corner.1) as i16),
+            geometry,
+        });
+    }
+
+    // Follow the pointer with the window being dragged
+    pub fn drag_to(&mut self, x: i16, y: i16) {
+        let Some(drag) = self.drag else {
+            return;
+        };
+        let geometry = drag.geometry_at(x, y);
+        let direction = (x as i32 - drag.pointer.0 as i32, y as i32 - drag.pointer.1 as i32);
+        if let Some(ws) = self.shown_workspace_of(drag.window) {
+            let geometry = match drag.kind {
+                DragKind::Move => ws.snap_move(drag.window, geometry, direction, false),
+                DragKind::Resize {right, bottom, ..} => drag.constrain(ws.snap_resize(drag.window, geometry, right, bottom, direction, false))
+            };
+            ws.move_floating(drag.window, geometry);
+        }
+    }
+
+    pub fn end_drag(&mut self) {
+        self.drag = None;
+    }
+
+    // The workspace on screen that holds the given window
+    fn shown_workspace_of(&mut self, win: u32) -> Option<&mut Workspace<'a>> {
+        self.monitors.iter_mut()
+            .map(|mon| mon.workspace_mut())
+            .find(|ws| ws.windows.contains(&win))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: Rect = Rect {x: 100, y: 100, width: 400, height: 300};
+
+    fn resize(right: bool, bottom: bool, hints: SizeHints) -> Drag {
+        let pointer = ((START.x + if right {START.width} else {0}) as i16, (START.y + if bottom {START.height} else {0}) as i16);
+        Drag {window: 1, kind: DragKind::Resize {right, bottom, hints}, pointer, geometry: START}
+    }
+
+    #[test]
+    fn move_follows_the_pointer() {
+        let drag = Drag {window: 1, kind: DragKind::Move, pointer: (10, 10), geometry: START};
+        let rect = drag.geometry_at(30, -20);
+        assert_eq!(rect, Rect::new(120, 70, 400, 300));
+        assert_eq!(drag.constrain(rect), rect);
+    }
+
+    #[test]
+    fn resize_moves_only_the_dragged_corner() {
+        let drag = resize(true, true, SizeHints::default());
+        assert_eq!(drag.geometry_at(550, 420), Rect::new(100, 100, 450, 320));
+
+        let drag = resize(false, false, SizeHints::default());
+        assert_eq!(drag.geometry_at(80, 90), Rect::new(80, 90, 420, 310));
+
+        let drag = resize(true, false, SizeHints::default());
+        assert_eq!(drag.geometry_at(450, 150), Rect::new(100, 150, 350, 250));
+    }
+
+    #[test]
+    fn constrain_keeps_the_opposite_corner() {
+        let hints = SizeHints {increment: Some((10, 20)), ..SizeHints::default()};
+
+        let drag = resize(false, false, hints);
+        let rect = drag.constrain(drag.geometry_at(75, 85));
+        assert_eq!((rect.width, rect.height), (420, 300));
+        assert_eq!((rect.x + rect.width, rect.y + rect.height), (500, 400));
+
+        let drag = resize(true, true, hints);
+        let rect = drag.constrain(drag.geometry_at(527, 413));
+        assert_eq!(rect, Rect::new(100, 100, 420, 300));
+    }
+
+    #[test]
+    fn constrain_stops_at_the_minimum_size() {
+        let hints = SizeHints {min: Some((200, 150)), ..SizeHints::default()};
+        let drag = resize(false, true, hints);
+        let rect = drag.constrain(drag.geometry_at(450, 200));
+        assert_eq!(rect, Rect::new(300, 100, 200, 150));
+    }
+}
@@ -1,9 +1,11 @@
 
 
-use x11rb::{protocol::{Event, xproto::{MapRequestEvent, UnmapNotifyEvent, ButtonPressEvent, ButtonReleaseEvent, ConnectionExt, SetMode, KeyPressEvent, KeyReleaseEvent, ConfigureRequestEvent, ConfigureWindowAux}}};
+use x11rb::{protocol::{Event, xproto::{MapRequestEvent, UnmapNotifyEvent, ButtonPressEvent, ButtonReleaseEvent, ConnectionExt, SetMode, KeyPressEvent, KeyReleaseEvent, ConfigureRequestEvent, ConfigureWindowAux, ExposeEvent, PropertyNotifyEvent, DestroyNotifyEvent, MotionNotifyEvent, ClientMessageEvent, AtomEnum, ChangeWindowAttributesAux, EventMask}}};
 use x11rb::connection::Connection;
 
 use super::WindowManager;
+use super::layout::Rect;
+use super::client::{Client, Layer, window_class, window_size_hints, window_atoms, window_transient_for, NORMAL_STATE};
 
 
 
@@ -11,35 +13,91 @@ impl<'a> WindowManager<'a>{
 
     // Match event type and call appropriate handler
     pub fn handle_event(&mut self, event: Event){
+        // Only events that can map, unmap, focus or move windows between layers change the stacking.
+        // Notify events never do, and restacking on them would answer our own restacks forever.
+        let restack = matches!(event, Event::MapRequest(_) | Event::UnmapNotify(_) | Event::DestroyNotify(_)
+            | Event::ButtonPress(_) | Event::KeyPress(_) | Event::ConfigureRequest(_) | Event::ClientMessage(_));
         match event {
             Event::MapRequest(event) => self.handle_map_request(event),
             Event::UnmapNotify(event) => self.handle_unmap_notify(event),
+            Event::DestroyNotify(event) => self.handle_destroy_notify(event),
             Event::ButtonPress(event) => self.handle_button_press(event),
             Event::ButtonRelease(event) => self.handle_button_release(event),
+            Event::MotionNotify(event) => self.handle_motion_notify(event),
             Event::KeyPress(event) => self.handle_key_press(event),
             Event::KeyRelease(event) => self.handle_key_release(event),
             Event::ConfigureRequest(event) => self.handle_configure_request(event),
+            Event::Expose(event) => self.handle_expose(event),
+            Event::PropertyNotify(event) => self.handle_property_notify(event),
+            Event::ClientMessage(event) => self.handle_client_message(event),
             _ => {}
         }
+        if restack {
+            self.restack();
+        }
     }
 
     // Handle adding a new window to the manager
     fn handle_map_request(&mut self, event: MapRequestEvent){
+        // A minimized window mapping itself asks to be restored, and other managed windows stay as they are
+        if let Some(ws) = self.monitors.iter_mut().flat_map(|mon| mon.workspaces.iter_mut()).find(|ws| ws.minimized.contains(&event.window)) {
+            ws.restore(event.window);
+            self.publish_clients();
+            return;
+        }
+        if self.client_mut(event.window).is_some() || self.layered.iter().any(|(win, _)| *win == event.window) {
+            return;
+        }
         println!("Adding Window: {:?}", event.window);
         self.config.connection.grab_server().expect("Unable To Grab Server");
         self.config.connection.change_save_set(SetMode::INSERT, event.window).expect("Unable To Change Save Set");
+        self.config.connection.change_window_attributes(event.window, &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE)).expect("Unable To Set Attributes");
         self.config.connection.map_window(event.window).expect("Unable To Map Window");
         self.config.connection.ungrab_server().expect("Unable To Ungrab Server");
-        self.workspaces[self.focused].add_window(event.window);
+        let mut client = Client::new(event.window);
+        client.class = window_class(self.config, event.window);
+        client.set_state(self.config, NORMAL_STATE);
+        client.transient_for = window_transient_for(self.config, event.window);
+        client.hints = window_size_hints(self.config, event.window);
+        client.honor_hints = self.config.rules.iter().any(|rule| rule.class == client.class && rule.size_hints);
+        let types = window_atoms(self.config, event.window, self.config.window_type_atom);
+        let states = window_atoms(self.config, event.window, self.config.net_wm_state_atom);
+        let layer_of = |atoms: &[u32], layers: &[(u32, Layer)]| atoms.iter().find_map(|atom| layers.iter().find(|(layer_atom, _)| layer_atom == atom).map(|(_, layer)| *layer));
+        if let Some(layer) = layer_of(&types, &self.config.layer_type_atoms) {
+            // Desktops, docks and notifications keep the place they asked for and are only stacked
+            self.layered.push((event.window, layer));
+            return;
+        }
+        client.requested_layer = layer_of(&states, &self.config.layer_state_atoms);
+        client.layer = client.requested_layer;
+        if self.config.scratchpads.iter().any(|scratchpad| scratchpad.class == client.class) {
+            client.scratchpad = true;
+            self.show_scratchpad(client, Vec::new());
+        } else if client.transient_for.is_some() || client.hints.is_fixed() || types.iter().any(|kind| self.config.floating_type_atoms.contains(kind)) {
+            self.show_dialog(client);
+        } else {
+            self.workspace().add_client(client);
+        }
         self.tile_windows();
         self.set_focus(0);
+        self.publish_clients();
     }
 
-    // Handle removing a window from the manager
+    // Handle removing a window from the manager, unless the window manager unmapped it itself
     fn handle_unmap_notify(&mut self, event: UnmapNotifyEvent){
-        for ws in self.workspaces.iter_mut() {
-            ws.remove_window(event.window);
+        if let Some(client) = self.client_mut(event.window) {
+            if client.pending_unmaps > 0 {
+                client.pending_unmaps -= 1;
+                return;
+            }
+            client.mapped = false;
         }
+        self.forget_window(event.window);
+    }
+
+    // Handle a window being destroyed, which may happen while it is hidden
+    fn handle_destroy_notify(&mut self, event: DestroyNotifyEvent){
+        self.forget_window(event.window);
     }
 
     // Handle mouse button pressing
@@ -47,12 +105,20 @@ impl<'a> WindowManager<'a>{
         println!("Button Pressed: {:?}", event.detail);
         if event.event != self.config.connection.setup().roots[self.config.screen_num].root {
             self.focus_window_id(event.event);
+        } else if event.child != x11rb::NONE && u16::from(event.state) & self.config.mouse_modkey == self.config.mouse_modkey && (event.detail == 1 || event.detail == 3) {
+            self.start_drag(event.child, event.detail == 3, event.root_x, event.root_y);
         }
     }
 
     // Handle mouse button releasing
     fn handle_button_release(&mut self, event: ButtonReleaseEvent){
         println!("Button Released: {:?}", event.detail);
+        self.end_drag();
+    }
+
+    // Handle the pointer moving while a window is dragged
+    fn handle_motion_notify(&mut self, event: MotionNotifyEvent){
+        self.drag_to(event.root_x, event.root_y);
     }
 
     // Handle key pressing
@@ -70,9 +136,89 @@ impl<'a> WindowManager<'a>{
         println!("Key Released: {:?}", event.detail);
     }
 
-    // Handle window configuration requests
+    // Handle window configuration requests. Floating windows keep the geometry they ask for, and
+    // tiled windows are put back in their tiles.
     fn handle_configure_request(&mut self, event: ConfigureRequestEvent){
-        self.config.connection.configure_window(event.window, &ConfigureWindowAux::from_configure_request(&event)).expect("Unable to configure window");
+        let mut aux = ConfigureWindowAux::from_configure_request(&event);
+        if let Some(client) = self.client_mut(event.window) {
+            // Managed windows are stacked by their layer, not by where they ask to go
+            aux.sibling = None;
+            aux.stack_mode = None;
+            if client.floating {
+                let geometry = client.geometry;
+                client.geometry = Rect::new(
+                    aux.x.unwrap_or(geometry.x),
+                    aux.y.unwrap_or(geometry.y),
+                    aux.width.map_or(geometry.width, |width| width as i32),
+                    aux.height.map_or(geometry.height, |height| height as i32),
+                );
+            }
+        }
+        self.config.connection.configure_window(event.window, &aux).expect("Unable to configure window");
         self.tile_windows();
     }
+
+    // Handle redrawing the tab strips
+    fn handle_expose(&mut self, event: ExposeEvent){
+        if event.count != 0 {
+            return;
+        }
+        for ws in self.monitors.iter().flat_map(|mon| mon.workspaces.iter()) {
+            if ws.has_bar(event.window) {
+                ws.draw_tabs();
+            }
+        }
+    }
+
+    // Handle window property changes
+    fn handle_property_notify(&mut self, event: PropertyNotifyEvent){
+        if event.atom == u32::from(AtomEnum::WM_NAME) || event.atom == self.config.net_wm_name_atom {
+            for ws in self.monitors.iter().flat_map(|mon| mon.workspaces.iter()) {
+                if ws.windows.contains(&event.window) {
+                    ws.draw_tabs();
+                }
+            }
+        } else if event.atom == u32::from(AtomEnum::WM_NORMAL_HINTS) {
+            let hints = window_size_hints(self.config, event.window);
+            for ws in self.monitors.iter_mut().flat_map(|mon| mon.workspaces.iter_mut()) {
+                ws.set_size_hints(event.window, hints);
+            }
+            if let Some(client) = self.scratchpad.iter_mut().find(|client| client.window == event.window) {
+                client.hints = hints;
+            }
+        }
+    }
+
+    // Handle a window asking to change its state, such as a picture-in-picture window asking to be
+    // kept above the others
+    fn handle_client_message(&mut self, event: ClientMessageEvent){
+        if event.type_ != self.config.net_wm_state_atom {
+            return;
+        }
+        // The action to take, then up to two states it applies to
+        let [action, first, second, ..] = event.data.as_data32();
+        let config = self.config;
+        let Some(client) = self.client_mut(event.window) else {
+            return;
+        };
+        for (_, layer) in config.layer_state_atoms.iter().filter(|(atom, _)| *atom == first || *atom == second) {
+            let add = match action {
+                0 => false,
+                1 => true,
+                _ => client.layer != Some(*layer)
+            };
+            if add {
+                client.requested_layer = Some(*layer);
+                client.layer = Some(*layer);
+            } else {
+                if client.requested_layer == Some(*layer) {
+                    client.requested_layer = None;
+                }
+                if client.layer == Some(*layer) {
+                    client.layer = client.requested_layer;
+                }
+            }
+        }
+        client.set_layer_state(config);
+    }
 }
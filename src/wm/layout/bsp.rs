@@ -0,0 +1,259 @@
+use super::{Layout, LayoutMessage, LayoutParams, Rect, Split};
+
+// A node of the split tree, either a single window or two subtrees sharing an area
+enum Node {
+    Leaf(u32),
+    Split {split: Split, ratio: f32, first: Box<Node>, second: Box<Node>},
+}
+
+impl Node {
+    fn contains(&self, win: u32) -> bool {
+        match self {
+            Node::Leaf(leaf) => *leaf == win,
+            Node::Split {first, second, ..} => first.contains(win) || second.contains(win),
+        }
+    }
+
+    fn first_leaf(&self) -> u32 {
+        match self {
+            Node::Leaf(leaf) => *leaf,
+            Node::Split {first, ..} => first.first_leaf(),
+        }
+    }
+
+    // Drop every leaf not in the given list, collapsing splits left with one child
+    fn retain(self, windows: &[u32]) -> Option<Node> {
+        match self {
+            Node::Leaf(leaf) => if windows.contains(&leaf) {Some(Node::Leaf(leaf))} else {None},
+            Node::Split {split, ratio, first, second} => {
+                match (first.retain(windows), second.retain(windows)) {
+                    (Some(first), Some(second)) => Some(Node::Split {split, ratio, first: Box::new(first), second: Box::new(second)}),
+                    (Some(only), None) | (None, Some(only)) => Some(only),
+                    (None, None) => None,
+                }
+            }
+        }
+    }
+
+    // Replace the leaf holding target with a split between it and the new window
+    fn insert(&mut self, target: u32, win: u32, split: Split) -> bool {
+        match self {
+            Node::Leaf(leaf) if *leaf == target => {
+                *self = Node::Split {split, ratio: 0.5, first: Box::new(Node::Leaf(target)), second: Box::new(Node::Leaf(win))};
+                true
+            },
+            Node::Leaf(_) => false,
+            Node::Split {first, second, ..} => first.insert(target, win, split) || second.insert(target, win, split),
+        }
+    }
+
+    // Find the split directly above the leaf holding the given window
+    fn parent_of(&mut self, win: u32) -> Option<&mut Node> {
+        let is_parent = match self {
+            Node::Leaf(_) => return None,
+            Node::Split {first, second, ..} => matches!(**first, Node::Leaf(leaf) if leaf == win) || matches!(**second, Node::Leaf(leaf) if leaf == win),
+        };
+        if is_parent {
+            return Some(self);
+        }
+        match self {
+            Node::Split {first, second, ..} => {
+                if first.contains(win) {first.parent_of(win)} else {second.parent_of(win)}
+            },
+            Node::Leaf(_) => None,
+        }
+    }
+
+    // Turn the subtree a quarter turn clockwise
+    fn rotate(&mut self) {
+        if let Node::Split {split, first, second, ..} = self {
+            if *split == Split::Vertical {
+                std::mem::swap(first, second);
+            }
+            *split = match split {
+                Split::Horizontal => Split::Vertical,
+                Split::Vertical => Split::Horizontal,
+            };
+            first.rotate();
+            second.rotate();
+        }
+    }
+
+    fn arrange(&self, area: Rect, params: &LayoutParams, rects: &mut Vec<(u32, Rect)>) {
+        match self {
+            Node::Leaf(leaf) => rects.push((*leaf, params.window_rect(area))),
+            Node::Split {split, ratio, first, second} => {
+                let (a, b) = match split {
+                    Split::Horizontal => area.split_left((area.width as f32 * ratio) as i32),
+                    Split::Vertical => area.split_top((area.height as f32 * ratio) as i32),
+                };
+                first.arrange(a, params, rects);
+                second.arrange(b, params, rects);
+            }
+        }
+    }
+}
+
+// Manual tiling, where each new window splits the focused one in the preselected direction
+pub struct Bsp {
+    root: Option<Node>, // Tree of splits, None while the workspace is empty
+    split: Split, // Direction the next window will split in
+    insert_at: Option<u32>, // Window the next new window will split
+}
+
+impl Bsp {
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            split: Split::Horizontal,
+            insert_at: None,
+        }
+    }
+}
+
+impl Default for Bsp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Layout for Bsp {
+    fn name(&self) -> &'static str {
+        "Bsp"
+    }
+
+    fn arrange(&self, params: &LayoutParams) -> Vec<Rect> {
+        let area = params.usable();
+        let mut placed = Vec::new();
+        if let Some(root) = &self.root {
+            root.arrange(area, params, &mut placed);
+        }
+        params.windows.iter().map(|win| {
+            placed.iter().find(|(leaf, _)| leaf == win).map(|(_, rect)| *rect).unwrap_or_else(|| params.window_rect(area))
+        }).collect()
+    }
+
+    fn sync(&mut self, windows: &[u32], focused: Option<u32>) {
+        self.root = self.root.take().and_then(|root| root.retain(windows));
+        if let Some(win) = focused {
+            if self.root.as_ref().is_some_and(|root| root.contains(win)) {
+                self.insert_at = Some(win);
+            }
+        }
+
+        for win in windows.iter() {
+            match &mut self.root {
+                None => self.root = Some(Node::Leaf(*win)),
+                Some(root) => {
+                    if root.contains(*win) {
+                        continue;
+                    }
+                    let target = self.insert_at.filter(|target| root.contains(*target)).unwrap_or_else(|| root.first_leaf());
+                    root.insert(target, *win, self.split);
+                }
+            }
+            self.insert_at = Some(*win);
+        }
+    }
+
+    fn message(&mut self, message: &LayoutMessage, focused: Option<u32>) -> bool {
+        if let LayoutMessage::SetSplit(split) = message {
+            self.split = *split;
+            return false;
+        }
+        let (Some(root), Some(win)) = (&mut self.root, focused) else {
+            return false;
+        };
+        let Some(parent) = root.parent_of(win) else {
+            return false;
+        };
+        match message {
+            LayoutMessage::Flip => {
+                if let Node::Split {first, second, ..} = parent {
+                    std::mem::swap(first, second);
+                }
+            },
+            LayoutMessage::Rotate => parent.rotate(),
+            LayoutMessage::SetSplit(_) => {}
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{assert_tiled, params};
+
+    // Sync the windows in one at a time, each one focused after it is added, as a workspace does
+    fn bsp_with(windows: &[u32]) -> Bsp {
+        let mut bsp = Bsp::new();
+        for i in 0..windows.len() {
+            bsp.sync(&windows[..=i], i.checked_sub(1).map(|prev| windows[prev]));
+        }
+        bsp
+    }
+
+    #[test]
+    fn new_windows_split_the_focused_one() {
+        let bsp = bsp_with(&[1, 2]);
+        let rects = bsp.arrange(&params(&[1, 2], 1));
+        assert_tiled(&rects);
+        assert!(rects[0].x < rects[1].x);
+        assert_eq!(rects[0].y, rects[1].y);
+    }
+
+    #[test]
+    fn new_window_splits_the_window_focused_before_it() {
+        let mut bsp = bsp_with(&[1, 2]);
+        // Focus moves back to the first window, then a new window arrives and is focused before tiling
+        bsp.sync(&[1, 2], Some(1));
+        bsp.sync(&[3, 1, 2], Some(3));
+        let rects = bsp.arrange(&params(&[3, 1, 2], 1));
+        assert_tiled(&rects);
+        assert!(rects[1].x < rects[0].x && rects[0].x < rects[2].x);
+    }
+
+    #[test]
+    fn set_split_changes_the_next_split_only() {
+        let mut bsp = bsp_with(&[1, 2]);
+        assert!(!bsp.message(&LayoutMessage::SetSplit(Split::Vertical), Some(2)));
+        bsp.sync(&[1, 2, 3], Some(2));
+        let rects = bsp.arrange(&params(&[1, 2, 3], 1));
+        assert_tiled(&rects);
+        assert_eq!(rects[1].x, rects[2].x);
+        assert!(rects[1].y < rects[2].y);
+    }
+
+    #[test]
+    fn flip_swaps_the_halves() {
+        let mut bsp = bsp_with(&[1, 2]);
+        assert!(bsp.message(&LayoutMessage::Flip, Some(1)));
+        let rects = bsp.arrange(&params(&[1, 2], 1));
+        assert!(rects[0].x > rects[1].x);
+    }
+
+    #[test]
+    fn rotate_turns_the_split() {
+        let mut bsp = bsp_with(&[1, 2]);
+        assert!(bsp.message(&LayoutMessage::Rotate, Some(1)));
+        let rects = bsp.arrange(&params(&[1, 2], 1));
+        assert_tiled(&rects);
+        assert_eq!(rects[0].x, rects[1].x);
+    }
+
+    #[test]
+    fn removed_windows_give_their_space_back() {
+        let mut bsp = bsp_with(&[1, 2, 3]);
+        bsp.sync(&[1, 3], Some(1));
+        let params = params(&[1, 3], 1);
+        let rects = bsp.arrange(&params);
+        assert_eq!(rects.len(), 2);
+        assert_tiled(&rects);
+        // The two windows span the whole area again
+        let full = params.window_rect(params.usable());
+        assert_eq!(rects[0].x, full.x);
+        assert_eq!(rects[1].x + rects[1].width, full.x + full.width);
+        assert_eq!(rects[0].height, full.height);
+    }
+}
@@ -0,0 +1,106 @@
+use super::{Layout, LayoutParams, Rect};
+
+// The master column in the middle, with the other windows shared between a column on either side.
+// With a single stack window there is nothing to balance, so it falls back to master on the left.
+pub struct CenteredMaster;
+
+impl Layout for CenteredMaster {
+    fn name(&self) -> &'static str {
+        "CenteredMaster"
+    }
+
+    fn arrange(&self, params: &LayoutParams) -> Vec<Rect> {
+        let count = params.windows.len();
+        let area = params.oriented_area();
+        let masters = params.nmaster.min(count);
+        let stack = count - masters;
+        if stack == 0 {
+            return params.orient(area.weighted_rows(&params.weights).into_iter().map(|tile| params.window_rect(tile)).collect());
+        }
+
+        let master_width = if masters == 0 {0} else {params.master_size(area.width)};
+        let mut rects = Vec::with_capacity(count);
+        if stack == 1 {
+            let (master, side) = area.split_left(master_width);
+            if masters > 0 {
+                rects.extend(master.weighted_rows(&params.weights[..masters]).into_iter().map(|tile| params.window_rect(tile)));
+            }
+            rects.push(params.window_rect(side));
+            return params.orient(rects);
+        }
+
+        let (left, rest) = area.split_left((area.width - master_width)/2);
+        let (master, right) = rest.split_left(master_width);
+        if masters > 0 {
+            rects.extend(master.weighted_rows(&params.weights[..masters]).into_iter().map(|tile| params.window_rect(tile)));
+        }
+
+        // Alternate the stack windows between the right and left columns, starting on the right
+        let stack_weights = &params.weights[masters..];
+        let right_weights: Vec<f32> = stack_weights.iter().step_by(2).copied().collect();
+        let left_weights: Vec<f32> = stack_weights.iter().skip(1).step_by(2).copied().collect();
+        let mut right_tiles = right.weighted_rows(&right_weights).into_iter();
+        let mut left_tiles = left.weighted_rows(&left_weights).into_iter();
+        for i in 0..stack {
+            let tile = if i % 2 == 0 {right_tiles.next()} else {left_tiles.next()};
+            rects.push(params.window_rect(tile.expect("Stack tile missing")));
+        }
+        params.orient(rects)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{assert_tiled, params, windows};
+
+    #[test]
+    fn one_stack_window_falls_back_to_master_on_the_left() {
+        let rects = CenteredMaster.arrange(&params(&[1, 2], 1));
+        assert_tiled(&rects);
+        assert!(rects[0].x < rects[1].x);
+    }
+
+    #[test]
+    fn master_sits_between_the_stack_columns() {
+        let windows = windows(5);
+        let rects = CenteredMaster.arrange(&params(&windows, 1));
+        assert_eq!(rects.len(), 5);
+        assert_tiled(&rects);
+        // Stack windows alternate right, left, right, left
+        assert!(rects[1].x > rects[0].x && rects[3].x > rects[0].x);
+        assert!(rects[2].x < rects[0].x && rects[4].x < rects[0].x);
+    }
+
+    #[test]
+    fn nmaster_larger_than_window_count() {
+        let windows = windows(3);
+        let rects = CenteredMaster.arrange(&params(&windows, 5));
+        assert_eq!(rects.len(), 3);
+        assert_tiled(&rects);
+    }
+
+    #[test]
+    fn no_masters() {
+        let windows = windows(4);
+        let rects = CenteredMaster.arrange(&params(&windows, 0));
+        assert_eq!(rects.len(), 4);
+        assert_tiled(&rects);
+    }
+
+    #[test]
+    fn side_columns_split_the_rest_evenly() {
+        let windows = windows(7);
+        let mut params = params(&windows, 1);
+        params.master_ratio = 0.6;
+        let rects = CenteredMaster.arrange(&params);
+        assert_tiled(&rects);
+        // The master gets its ratio and the columns either side of it the same share of the rest
+        assert!(rects[0].width > rects[1].width + rects[2].width);
+        assert!((rects[1].width - rects[2].width).abs() <= 1);
+        // Three windows on each side, lined up row by row
+        for pair in rects[1..].chunks(2) {
+            assert_eq!((pair[0].y, pair[0].height), (pair[1].y, pair[1].height));
+        }
+    }
+}
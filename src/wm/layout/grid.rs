@@ -0,0 +1,71 @@
+use super::{Layout, LayoutParams, Rect};
+
+// Windows in a near-square grid, with the windows of a short last row sharing its width evenly
+pub struct Grid;
+
+impl Layout for Grid {
+    fn name(&self) -> &'static str {
+        "Grid"
+    }
+
+    fn arrange(&self, params: &LayoutParams) -> Vec<Rect> {
+        let count = params.windows.len();
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let mut columns = 1;
+        while columns * columns < count {
+            columns += 1;
+        }
+        let rows = count.div_ceil(columns);
+
+        let mut rects = Vec::with_capacity(count);
+        for (i, row) in params.usable().rows(rows).into_iter().enumerate() {
+            let in_row = columns.min(count - i*columns);
+            rects.extend(row.columns(in_row).into_iter().map(|tile| params.window_rect(tile)));
+        }
+        rects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{assert_tiled, params, windows};
+
+    #[test]
+    fn square_counts_make_square_grids() {
+        let windows = windows(9);
+        let rects = Grid.arrange(&params(&windows, 1));
+        assert_tiled(&rects);
+        let mut xs: Vec<i32> = rects.iter().map(|rect| rect.x).collect();
+        xs.sort();
+        xs.dedup();
+        assert_eq!(xs.len(), 3);
+    }
+
+    #[test]
+    fn short_last_row_shares_its_width() {
+        let windows = windows(5);
+        let rects = Grid.arrange(&params(&windows, 1));
+        assert_eq!(rects.len(), 5);
+        assert_tiled(&rects);
+        // Three columns on the first row, two wider ones on the last
+        assert_eq!(rects[3].y, rects[4].y);
+        assert!(rects[3].width > rects[0].width);
+    }
+
+    #[test]
+    fn counts_past_a_square_add_a_column() {
+        let windows = windows(10);
+        let rects = Grid.arrange(&params(&windows, 1));
+        assert_tiled(&rects);
+        // Four columns over three rows, the last row's two windows splitting it in half
+        assert!(rects[..4].iter().all(|rect| rect.y == rects[0].y && rect.width == rects[0].width));
+        assert_eq!(rects[4].y, rects[7].y);
+        assert!(rects[4].y > rects[0].y && rects[8].y > rects[4].y);
+        assert_eq!(rects[8].y, rects[9].y);
+        assert!(rects[8].width > 2*rects[0].width);
+    }
+}
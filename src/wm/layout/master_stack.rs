@@ -0,0 +1,72 @@
+use super::{Layout, LayoutParams, Rect};
+
+// The master windows on one side and every other window stacked on the other
+pub struct MasterStack;
+
+impl Layout for MasterStack {
+    fn name(&self) -> &'static str {
+        "MasterStack"
+    }
+
+    fn arrange(&self, params: &LayoutParams) -> Vec<Rect> {
+        let area = params.oriented_area();
+        let masters = params.nmaster.min(params.windows.len());
+        let stacked = params.windows.len() - masters;
+        if masters == 0 || stacked == 0 {
+            return params.orient(area.weighted_rows(&params.weights).into_iter().map(|tile| params.window_rect(tile)).collect());
+        }
+
+        let (master, stack) = area.split_left(params.master_size(area.width));
+        let mut rects: Vec<Rect> = master.weighted_rows(&params.weights[..masters]).into_iter().map(|tile| params.window_rect(tile)).collect();
+        rects.extend(stack.weighted_rows(&params.weights[masters..]).into_iter().map(|tile| params.window_rect(tile)));
+        params.orient(rects)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{assert_tiled, params, windows};
+
+    #[test]
+    fn master_takes_its_ratio() {
+        let params = params(&[1, 2, 3], 1);
+        let rects = MasterStack.arrange(&params);
+        assert_tiled(&rects);
+        assert!(rects[0].x < rects[1].x);
+        assert_eq!(rects[1].x, rects[2].x);
+        assert!(rects[1].y < rects[2].y);
+    }
+
+    #[test]
+    fn stack_windows_share_one_column_evenly() {
+        let windows = windows(12);
+        let rects = MasterStack.arrange(&params(&windows, 1));
+        let stack = &rects[1..];
+        assert!(stack.iter().all(|rect| rect.x == stack[0].x && rect.width == stack[0].width));
+        let heights: Vec<i32> = stack.iter().map(|rect| rect.height).collect();
+        // Every row is the same height, with the rounding left over going to the last
+        assert!(heights[..10].iter().all(|height| *height == heights[0]));
+        assert!(heights[10] >= heights[0] && heights[10] - heights[0] < 11);
+        // Nothing is left over below the last window
+        assert_eq!(rects[0].y + rects[0].height, stack[11 - 1].y + stack[11 - 1].height);
+    }
+
+    #[test]
+    fn nmaster_larger_than_window_count() {
+        let windows = windows(3);
+        let rects = MasterStack.arrange(&params(&windows, 5));
+        assert_eq!(rects.len(), 3);
+        assert_tiled(&rects);
+        // Every window is a master, so they all share one column
+        assert!(rects.iter().all(|rect| rect.x == rects[0].x && rect.width == rects[0].width));
+    }
+
+    #[test]
+    fn no_masters() {
+        let windows = windows(3);
+        let rects = MasterStack.arrange(&params(&windows, 0));
+        assert_eq!(rects.len(), 3);
+        assert_tiled(&rects);
+    }
+}
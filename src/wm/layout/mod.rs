@@ -0,0 +1,385 @@
+use self::bsp::Bsp;
+use self::centered_master::CenteredMaster;
+use self::grid::Grid;
+use self::master_stack::MasterStack;
+use self::monocle::Monocle;
+use self::spiral::Spiral;
+
+pub mod bsp;
+pub mod centered_master;
+pub mod grid;
+pub mod master_stack;
+pub mod monocle;
+pub mod spiral;
+
+// A rectangle on the screen, signed so layout math can't underflow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    // Shrink the rectangle by the given amount on every side
+    pub fn inset(&self, amount: i32) -> Self {
+        Self {
+            x: self.x + amount,
+            y: self.y + amount,
+            width: (self.width - amount*2).max(1),
+            height: (self.height - amount*2).max(1),
+        }
+    }
+
+    // Split into a left part of the given width and the right remainder
+    pub fn split_left(&self, width: i32) -> (Self, Self) {
+        let width = width.clamp(0, self.width);
+        (Self::new(self.x, self.y, width, self.height),
+         Self::new(self.x + width, self.y, self.width - width, self.height))
+    }
+
+    // Split into a top part of the given height and the bottom remainder
+    pub fn split_top(&self, height: i32) -> (Self, Self) {
+        let height = height.clamp(0, self.height);
+        (Self::new(self.x, self.y, self.width, height),
+         Self::new(self.x, self.y + height, self.width, self.height - height))
+    }
+
+    // Cut into `count` rows of (nearly) equal height, handing leftover pixels to the first rows
+    pub fn rows(&self, count: usize) -> Vec<Self> {
+        let count = count.max(1) as i32;
+        let mut y = self.y;
+        (0..count).map(|i| {
+            let height = self.height/count + if i < self.height%count {1} else {0};
+            let row = Self::new(self.x, y, self.width, height);
+            y += height;
+            row
+        }).collect()
+    }
+
+    // Cut into rows with heights in proportion to the given weights, handing leftover pixels to the last row
+    pub fn weighted_rows(&self, weights: &[f32]) -> Vec<Self> {
+        let total: f32 = weights.iter().sum();
+        if weights.is_empty() {
+            return Vec::new();
+        }
+        if total <= 0.0 {
+            return self.rows(weights.len());
+        }
+        let mut y = self.y;
+        weights.iter().enumerate().map(|(i, weight)| {
+            let height = if i == weights.len() - 1 {self.y + self.height - y} else {(self.height as f32 * weight / total) as i32};
+            let row = Self::new(self.x, y, self.width, height);
+            y += height;
+            row
+        }).collect()
+    }
+
+    // Cut into `count` columns of (nearly) equal width, handing leftover pixels to the first columns
+    pub fn columns(&self, count: usize) -> Vec<Self> {
+        let count = count.max(1) as i32;
+        let mut x = self.x;
+        (0..count).map(|i| {
+            let width = self.width/count + if i < self.width%count {1} else {0};
+            let column = Self::new(x, self.y, width, self.height);
+            x += width;
+            column
+        }).collect()
+    }
+}
+
+// Direction of a split. Horizontal places the two halves side by side, vertical stacks them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Split {
+    Horizontal,
+    Vertical,
+}
+
+// Which side of the workspace the master area sits on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Left,
+    Top,
+    Right,
+    Bottom,
+}
+
+impl Orientation {
+    // The next side going clockwise
+    pub fn next(self) -> Self {
+        match self {
+            Orientation::Left => Orientation::Top,
+            Orientation::Top => Orientation::Right,
+            Orientation::Right => Orientation::Bottom,
+            Orientation::Bottom => Orientation::Left,
+        }
+    }
+
+    // The area as a layout sees it when it places the master on the left.
+    // Top and bottom swap width and height, so rows of the layout become columns on screen.
+    pub fn frame(self, area: Rect) -> Rect {
+        match self {
+            Orientation::Left | Orientation::Right => area,
+            Orientation::Top | Orientation::Bottom => Rect::new(area.x, area.y, area.height, area.width),
+        }
+    }
+
+    // Map a rectangle laid out in frame(area) back onto the real area
+    pub fn place(self, area: Rect, rect: Rect) -> Rect {
+        let transposed = Rect::new(area.x + rect.y - area.y, area.y + rect.x - area.x, rect.height, rect.width);
+        match self {
+            Orientation::Left => rect,
+            Orientation::Right => Rect::new(2*area.x + area.width - rect.x - rect.width, rect.y, rect.width, rect.height),
+            Orientation::Top => transposed,
+            Orientation::Bottom => Rect::new(transposed.x, 2*area.y + area.height - transposed.y - transposed.height, transposed.width, transposed.height),
+        }
+    }
+}
+
+// Commands for layouts that keep state of their own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutMessage {
+    SetSplit(Split), // Split in this direction from now on
+    Flip, // Swap the two halves of the split holding the focused window
+    Rotate, // Turn the split holding the focused window a quarter turn
+}
+
+// Everything a layout needs to know to arrange a workspace
+pub struct LayoutParams<'a> {
+    pub area: Rect, // Area of the workspace
+    pub gap: u16, // Gap between windows and around the edges
+    pub border: u16, // Border width of each window
+    pub tab_height: u16, // Height of a strip of tabs
+    pub master_ratio: f32, // Fraction of the area given to the master windows
+    pub nmaster: usize, // Number of windows sharing the master area
+    pub orientation: Orientation, // Side of the area the master sits on
+    pub windows: &'a [u32], // Windows to arrange
+    pub weights: Vec<f32>, // Share of its column each window gets, in the same order as windows
+}
+
+impl<'a> LayoutParams<'a> {
+    // The area left to split up once the outer gap is taken off. Each tile gives up
+    // another half gap in window_rect, so the edges end up a full gap away.
+    pub fn usable(&self) -> Rect {
+        self.area.inset((self.gap - self.gap/2) as i32)
+    }
+
+    // The usable area as seen by a layout that places the master on the left
+    pub fn oriented_area(&self) -> Rect {
+        self.orientation.frame(self.usable())
+    }
+
+    // Map rectangles computed in oriented_area back onto the workspace
+    pub fn orient(&self, rects: Vec<Rect>) -> Vec<Rect> {
+        let area = self.usable();
+        rects.into_iter().map(|rect| self.orientation.place(area, rect)).collect()
+    }
+
+    // Turn a tile into the rectangle passed to configure_window, which excludes the border
+    pub fn window_rect(&self, tile: Rect) -> Rect {
+        tile.inset((self.gap/2 + self.border) as i32)
+    }
+
+    // Width of the master area within the given rectangle
+    pub fn master_size(&self, total: i32) -> i32 {
+        (total as f32 * self.master_ratio) as i32
+    }
+}
+
+// A strategy for placing windows on a workspace
+pub trait Layout {
+    // Name shown when switching layouts
+    fn name(&self) -> &'static str;
+
+    // Compute one window rectangle for each window in params.windows, in the same order
+    fn arrange(&self, params: &LayoutParams) -> Vec<Rect>;
+
+    // Bring the layout's own model up to date with the windows, called before every arrange and
+    // whenever focus moves
+    fn sync(&mut self, _windows: &[u32], _focused: Option<u32>) {}
+
+    // Handle a layout specific command, returning whether the windows need to be retiled
+    fn message(&mut self, _message: &LayoutMessage, _focused: Option<u32>) -> bool {
+        false
+    }
+
+    // Where to draw a strip of tabs for the windows, if the layout has one
+    fn tab_strip(&self, _params: &LayoutParams) -> Option<Rect> {
+        None
+    }
+}
+
+// The layouts every workspace can cycle through, in order
+pub fn defaults() -> Vec<Box<dyn Layout>> {
+    vec![
+        Box::new(MasterStack),
+        Box::new(Monocle),
+        Box::new(Grid),
+        Box::new(Spiral),
+        Box::new(CenteredMaster),
+        Box::new(Bsp::new()),
+    ]
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub const AREA: Rect = Rect {x: 0, y: 0, width: 1920, height: 1080};
+
+    // Parameters for arranging the given windows on AREA, with every window weighted the same
+    pub fn params(windows: &[u32], nmaster: usize) -> LayoutParams<'_> {
+        LayoutParams {
+            area: AREA,
+            gap: 16,
+            border: 2,
+            tab_height: 18,
+            master_ratio: 0.5,
+            nmaster,
+            orientation: Orientation::Left,
+            windows,
+            weights: vec![1.0; windows.len()],
+        }
+    }
+
+    // Windows 1 to count
+    pub fn windows(count: u32) -> Vec<u32> {
+        (1..=count).collect()
+    }
+
+    fn overlaps(a: &Rect, b: &Rect) -> bool {
+        a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+    }
+
+    // Check the rectangles are non-empty, stay inside AREA and don't cover each other
+    pub fn assert_tiled(rects: &[Rect]) {
+        for (i, rect) in rects.iter().enumerate() {
+            assert!(rect.width > 0 && rect.height > 0, "empty rect {:?}", rect);
+            assert!(rect.x >= AREA.x && rect.y >= AREA.y, "rect {:?} outside area", rect);
+            assert!(rect.x + rect.width <= AREA.x + AREA.width && rect.y + rect.height <= AREA.y + AREA.height, "rect {:?} outside area", rect);
+            for other in rects[i+1..].iter() {
+                assert!(!overlaps(rect, other), "{:?} overlaps {:?}", rect, other);
+            }
+        }
+    }
+
+    // Arrange the given windows with every default layout, syncing them in one at a time with each
+    // focused after it is added, as a workspace does
+    fn arrange_all(windows: &[u32], nmaster: usize) -> Vec<(Box<dyn Layout>, Vec<Rect>)> {
+        defaults().into_iter().map(|mut layout| {
+            for i in 0..windows.len() {
+                layout.sync(&windows[..=i], i.checked_sub(1).map(|prev| windows[prev]));
+            }
+            let rects = layout.arrange(&params(windows, nmaster));
+            (layout, rects)
+        }).collect()
+    }
+
+    #[test]
+    fn every_layout_handles_no_windows() {
+        for (layout, rects) in arrange_all(&[], 1) {
+            assert!(rects.is_empty(), "{} placed windows that don't exist", layout.name());
+        }
+    }
+
+    #[test]
+    fn a_single_window_gets_all_the_room_the_layout_has() {
+        let params = params(&[1], 1);
+        for (layout, rects) in arrange_all(&[1], 1) {
+            assert_eq!(rects.len(), 1, "{}", layout.name());
+            match layout.tab_strip(&params) {
+                None => assert_eq!(rects[0], params.window_rect(params.usable()), "{}", layout.name()),
+                Some(strip) => assert_tiled(&[strip, rects[0]]),
+            }
+        }
+    }
+
+    #[test]
+    fn tiling_layouts_fit_many_windows() {
+        for count in [7, 10] {
+            let windows = windows(count);
+            for (layout, rects) in arrange_all(&windows, 2) {
+                assert_eq!(rects.len(), count as usize, "{}", layout.name());
+                if layout.tab_strip(&params(&windows, 2)).is_none() {
+                    assert_tiled(&rects);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn inset_never_goes_below_one_pixel() {
+        assert_eq!(Rect::new(10, 10, 100, 50).inset(5), Rect::new(15, 15, 90, 40));
+        assert_eq!(Rect::new(0, 0, 4, 4).inset(5), Rect::new(5, 5, 1, 1));
+    }
+
+    #[test]
+    fn splits_clamp_to_the_rect() {
+        let rect = Rect::new(10, 20, 100, 50);
+        assert_eq!(rect.split_left(30), (Rect::new(10, 20, 30, 50), Rect::new(40, 20, 70, 50)));
+        assert_eq!(rect.split_left(200), (rect, Rect::new(110, 20, 0, 50)));
+        assert_eq!(rect.split_left(-5), (Rect::new(10, 20, 0, 50), rect));
+        assert_eq!(rect.split_top(20), (Rect::new(10, 20, 100, 20), Rect::new(10, 40, 100, 30)));
+        assert_eq!(rect.split_top(80), (rect, Rect::new(10, 70, 100, 0)));
+    }
+
+    #[test]
+    fn rows_hand_leftover_pixels_to_the_first_rows() {
+        let rows = Rect::new(0, 0, 10, 11).rows(3);
+        assert_eq!(rows.iter().map(|row| row.height).collect::<Vec<_>>(), vec![4, 4, 3]);
+        assert_eq!(rows.iter().map(|row| row.y).collect::<Vec<_>>(), vec![0, 4, 8]);
+        assert_eq!(Rect::new(0, 0, 10, 11).rows(0), vec![Rect::new(0, 0, 10, 11)]);
+    }
+
+    #[test]
+    fn columns_cover_the_rect() {
+        let columns = Rect::new(5, 0, 10, 7).columns(4);
+        assert_eq!(columns.iter().map(|column| column.width).collect::<Vec<_>>(), vec![3, 3, 2, 2]);
+        assert_eq!(columns.last().map(|column| column.x + column.width), Some(15));
+    }
+
+    #[test]
+    fn weighted_rows_follow_the_weights() {
+        let rect = Rect::new(0, 0, 10, 100);
+        assert!(rect.weighted_rows(&[]).is_empty());
+        assert_eq!(rect.weighted_rows(&[1.0]), vec![rect]);
+        let rows = rect.weighted_rows(&[1.0, 3.0]);
+        assert_eq!(rows, vec![Rect::new(0, 0, 10, 25), Rect::new(0, 25, 10, 75)]);
+        // The last row takes whatever rounding left over
+        let rows = rect.weighted_rows(&[1.0, 1.0, 1.0]);
+        assert_eq!(rows.iter().map(|row| row.height).sum::<i32>(), 100);
+        assert_eq!(rect.weighted_rows(&[0.0, 0.0]), rect.rows(2));
+    }
+
+    #[test]
+    fn orientation_places_the_master_on_each_side() {
+        let area = Rect::new(0, 0, 100, 50);
+        let master = Rect::new(0, 0, 30, 50);
+        assert_eq!(Orientation::Left.place(area, master), master);
+        assert_eq!(Orientation::Right.place(area, master), Rect::new(70, 0, 30, 50));
+
+        let frame = Orientation::Top.frame(area);
+        assert_eq!(frame, Rect::new(0, 0, 50, 100));
+        let master = Rect::new(0, 0, 30, 100);
+        assert_eq!(Orientation::Top.place(area, master), Rect::new(0, 0, 100, 30));
+        assert_eq!(Orientation::Bottom.place(area, master), Rect::new(0, 20, 100, 30));
+    }
+
+    #[test]
+    fn orientation_place_works_away_from_the_origin() {
+        let area = Rect::new(1920, 100, 100, 50);
+        assert_eq!(Orientation::Right.place(area, Rect::new(1920, 100, 30, 50)), Rect::new(1990, 100, 30, 50));
+        assert_eq!(Orientation::Top.place(area, Rect::new(1920, 100, 30, 100)), Rect::new(1920, 100, 100, 30));
+    }
+
+    #[test]
+    fn orientation_cycles_clockwise() {
+        assert_eq!(Orientation::Left.next(), Orientation::Top);
+        assert_eq!(Orientation::Left.next().next().next().next(), Orientation::Left);
+    }
+}
@@ -0,0 +1,27 @@
+use super::{Layout, LayoutParams, Rect};
+
+// Every window takes up the whole workspace, with a strip of tabs across the top
+pub struct Monocle;
+
+impl Monocle {
+    // Split the workspace into the tile holding the tab strip and the tile shared by the windows
+    fn tiles(params: &LayoutParams) -> (Rect, Rect) {
+        params.usable().split_top((params.tab_height + params.gap/2*2) as i32)
+    }
+}
+
+impl Layout for Monocle {
+    fn name(&self) -> &'static str {
+        "Monocle"
+    }
+
+    fn arrange(&self, params: &LayoutParams) -> Vec<Rect> {
+        let (_, windows) = Self::tiles(params);
+        params.windows.iter().map(|_| params.window_rect(windows)).collect()
+    }
+
+    fn tab_strip(&self, params: &LayoutParams) -> Option<Rect> {
+        let (strip, _) = Self::tiles(params);
+        Some(strip.inset((params.gap/2) as i32))
+    }
+}
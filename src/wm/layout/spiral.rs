@@ -0,0 +1,96 @@
+use super::{Layout, LayoutParams, Rect};
+
+// Each window takes part of the remaining area, turning clockwise so the windows spiral inwards.
+// The master takes the master ratio of the area, every window after it takes half of what is left.
+pub struct Spiral;
+
+impl Layout for Spiral {
+    fn name(&self) -> &'static str {
+        "Spiral"
+    }
+
+    fn arrange(&self, params: &LayoutParams) -> Vec<Rect> {
+        let count = params.windows.len();
+        let mut rest = params.oriented_area();
+        let mut rects = Vec::with_capacity(count);
+        for i in 0..count {
+            if i == count - 1 {
+                rects.push(params.window_rect(rest));
+                break;
+            }
+            let tile = match i % 4 {
+                0 => {
+                    let size = if i == 0 {params.master_size(rest.width)} else {rest.width/2};
+                    let (tile, remainder) = rest.split_left(size);
+                    rest = remainder;
+                    tile
+                },
+                1 => {
+                    let (tile, remainder) = rest.split_top(rest.height/2);
+                    rest = remainder;
+                    tile
+                },
+                2 => {
+                    let (remainder, tile) = rest.split_left(rest.width - rest.width/2);
+                    rest = remainder;
+                    tile
+                },
+                _ => {
+                    let (remainder, tile) = rest.split_top(rest.height - rest.height/2);
+                    rest = remainder;
+                    tile
+                }
+            };
+            rects.push(params.window_rect(tile));
+        }
+        params.orient(rects)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Orientation;
+    use super::super::tests::{assert_tiled, params, windows, AREA};
+
+    #[test]
+    fn turns_clockwise() {
+        let windows = windows(5);
+        let rects = Spiral.arrange(&params(&windows, 1));
+        assert_eq!(rects.len(), 5);
+        assert_tiled(&rects);
+        // Master on the left, then top right, bottom right corner, bottom left of the rest, and inwards
+        assert!(rects[1].x > rects[0].x);
+        assert!(rects[2].y > rects[1].y);
+        assert!(rects[3].x < rects[2].x);
+        assert!(rects[4].y < rects[3].y);
+    }
+
+    #[test]
+    fn each_window_halves_the_rest() {
+        let windows = windows(6);
+        let rects = Spiral.arrange(&params(&windows, 1));
+        assert_tiled(&rects);
+        // The second window takes the top half of the right side, the third the right half of what
+        // is below it, and so on
+        assert!((rects[1].height - rects[2].height).abs() <= 1);
+        assert!((rects[2].width - rects[3].width).abs() <= 1);
+        assert!((rects[3].height - rects[4].height).abs() <= 1);
+        assert!((rects[4].width - rects[5].width).abs() <= 1);
+    }
+
+    #[test]
+    fn right_orientation_mirrors_the_spiral() {
+        let windows = windows(5);
+        let left = Spiral.arrange(&params(&windows, 1));
+        let mut params = params(&windows, 1);
+        params.orientation = Orientation::Right;
+        let right = Spiral.arrange(&params);
+        assert_tiled(&right);
+        assert!(right[0].x > right[1].x);
+        for (left, right) in left.iter().zip(right.iter()) {
+            assert_eq!(right.x, 2*AREA.x + AREA.width - left.x - left.width);
+            assert_eq!((right.y, right.width, right.height), (left.y, left.width, left.height));
+        }
+    }
+}
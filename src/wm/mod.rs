@@ -4,20 +4,36 @@ use std::process::Child;
 use x11rb::protocol::xinerama::query_screens;
 use x11rb::rust_connection::ReplyError;
 use x11rb::connection::Connection;
+use x11rb::wrapper::ConnectionExt as _;
 use x11rb::protocol::{xproto::*, ErrorKind};
 use crate::config::Config;
 
+use self::client::{Client, Layer};
+use self::drag::Drag;
+use self::monitor::Monitor;
 use self::workspace::Workspace;
 
 
 pub mod event;
 pub mod actions;
 pub mod workspace;
+pub mod monitor;
+pub mod client;
+pub mod container;
+pub mod layout;
+pub mod tabbar;
+pub mod drag;
 
 pub struct WindowManager<'a>{
     config: &'a Config, // Reference to the config struct
-    workspaces: Vec<Workspace<'a>>, // Vector of windows to manage
-    focused: usize, // Index of the focused workspace
+    monitors: Vec<Monitor<'a>>, // Monitors, each with its own workspaces
+    focused: usize, // Index of the focused monitor
+    history: Vec<Vec<usize>>, // Ids of the previously viewed workspaces of each monitor, most recent last
+    next_workspace_id: usize, // Id given to the next workspace created
+    scratchpad: Vec<Client>, // Hidden windows waiting to be toggled back in
+    layered: Vec<(u32, Layer)>, // Desktops, docks and notifications, left unmanaged in the layer of their type
+    stacked: Vec<u32>, // Stacking order last applied, bottom to top
+    drag: Option<Drag>, // Window being moved or resized with the mouse
     procs: Vec<Child>, // Current running child processes spawned by the run action
 }
 
@@ -41,8 +57,14 @@ impl<'a> WindowManager<'a>{
         // Return the new window manager
         Self{
             config,
-            workspaces: Vec::new(),
+            monitors: Vec::new(),
             focused: 0,
+            history: Vec::new(),
+            next_workspace_id: 0,
+            scratchpad: Vec::new(),
+            layered: Vec::new(),
+            stacked: Vec::new(),
+            drag: None,
             procs: Vec::new()
         }
     }
@@ -51,19 +73,35 @@ impl<'a> WindowManager<'a>{
     // Start the window manager
     pub fn start(&mut self){
 
-        // Query screens and build workspaces
+        // Query screens and build monitors
         let screen_list = query_screens(&self.config.connection)
             .expect("Unable to query screens")
             .reply().expect("Unable to query screens")
             .screen_info;
 
-        for s in screen_list.iter() {
-            self.workspaces.push(Workspace::new(s, self.config));
-        }
-        if self.workspaces.is_empty() {
+        if screen_list.is_empty() {
             panic!("No screens available");
         }
-        self.workspaces[self.focused].set_active(true);
+
+        // Workspaces are shared by all monitors, with their ids following the config order.
+        // Each monitor starts on a workspace of its own and the first holds the rest.
+        let mut names = self.config.workspaces.clone();
+        while names.len() < screen_list.len() {
+            names.push((names.len() + 1).to_string());
+        }
+        let mut workspaces: Vec<Workspace<'a>> = names.into_iter().enumerate()
+            .map(|(id, name)| Workspace::new(id, name, &screen_list[0], self.config))
+            .collect();
+        self.next_workspace_id = workspaces.len();
+        let mut rest = workspaces.split_off(screen_list.len());
+        for (s, ws) in screen_list.iter().zip(workspaces) {
+            let mut held = vec![ws];
+            held.append(&mut rest);
+            self.monitors.push(Monitor::new(s, held));
+            self.history.push(Vec::new());
+        }
+        self.workspace().set_active(true);
+        self.publish_clients();
 
         // Grab all keybindings
         for kb in &self.config.keybindings {
@@ -76,6 +114,7 @@ impl<'a> WindowManager<'a>{
                 GrabMode::ASYNC
             ).expect("Unable to grab key");
         }
+        self.grab_drag_buttons();
         // Start event loop
         loop{
             // Wait for a new event
@@ -100,26 +139,79 @@ impl<'a> WindowManager<'a>{
         }
     }
 
+    // The workspace shown on the focused monitor
+    pub fn workspace(&mut self) -> &mut Workspace<'a> {
+        self.monitors[self.focused].workspace_mut()
+    }
+
+    // Find the state of a managed window, wherever it is kept
+    pub fn client_mut(&mut self, win: u32) -> Option<&mut Client> {
+        self.monitors.iter_mut()
+            .flat_map(|mon| mon.workspaces.iter_mut())
+            .find_map(|ws| ws.clients.get_mut(&win))
+            .or_else(|| self.scratchpad.iter_mut().find(|client| client.window == win))
+    }
+
+    // Stop managing a window
+    pub fn forget_window(&mut self, win: u32){
+        for ws in self.monitors.iter_mut().flat_map(|mon| mon.workspaces.iter_mut()) {
+            ws.remove_window(win);
+        }
+        self.scratchpad.retain(|client| client.window != win);
+        self.layered.retain(|(window, _)| *window != win);
+        self.publish_clients();
+    }
+
+    // List every managed window on the root window in _NET_CLIENT_LIST, for status bars to find the
+    // windows whose _NET_WM_STATE_HIDDEN marks them minimized
+    pub fn publish_clients(&self){
+        let clients: Vec<u32> = self.monitors.iter()
+            .flat_map(|mon| mon.workspaces.iter())
+            .flat_map(|ws| ws.windows.iter().chain(ws.hidden.iter()).chain(ws.minimized.iter()).copied())
+            .chain(self.scratchpad.iter().map(|client| client.window))
+            .collect();
+        let root = self.config.connection.setup().roots[self.config.screen_num].root;
+        self.config.connection.change_property32(PropMode::REPLACE, root, self.config.client_list_atom, AtomEnum::WINDOW, &clients)
+            .expect("Unable to publish client list");
+    }
+
+    // Stack the windows shown on every monitor in one pass, bottom to top by layer, along with the
+    // unmanaged windows. Nothing is sent when the order is the one already applied.
+    pub fn restack(&mut self){
+        let mut order: Vec<(Layer, u32)> = self.monitors.iter().flat_map(|mon| mon.workspace().stacking()).collect();
+        order.extend(self.layered.iter().map(|(win, layer)| (*layer, *win)));
+        order.sort_by_key(|(layer, _)| *layer);
+        let order: Vec<u32> = order.into_iter().map(|(_, win)| win).collect();
+        if order == self.stacked {
+            return;
+        }
+        for win in order.iter() {
+            self.config.connection.configure_window(*win, &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE)).expect("Unable to raise window");
+        }
+        self.stacked = order;
+    }
+
     pub fn tile_windows(&mut self){
-        for ws in self.workspaces.iter_mut() {
-            ws.tile();
+        for mon in self.monitors.iter_mut() {
+            mon.workspace_mut().tile();
         }
 
     }
 
     pub fn set_focus(&mut self, index: usize){
-        self.workspaces[self.focused].set_focus(index);
+        self.workspace().set_focus(index);
     }
 
     pub fn set_master_width(&mut self, width: u16){
-        self.workspaces[self.focused].set_master_width(width);
+        self.workspace().set_master_width(width);
     }
 
     pub fn focus_window_id(&mut self, win: u32){
-        for i in 0..self.workspaces.len() {
-            if self.workspaces[i].focus_window_id(win) {
+        for i in 0..self.monitors.len() {
+            if self.monitors[i].workspace_mut().focus_window_id(win) && i != self.focused {
+                self.workspace().set_active(false);
                 self.focused = i;
             }
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,110 @@
+use x11rb::protocol::xinerama::ScreenInfo;
+
+use crate::config::Config;
+
+use super::workspace::Workspace;
+
+// A physical screen, showing one of the workspaces it holds at a time
+pub struct Monitor<'a> {
+    pub x: i16, // X coordinate of the monitor
+    pub y: i16, // Y coordinate of the monitor
+    pub width: u16, // Width of the monitor
+    pub height: u16, // Height of the monitor
+    pub workspaces: Vec<Workspace<'a>>, // Workspaces the monitor holds, which can move to other monitors
+    pub current: usize, // Index of the workspace being shown
+}
+
+impl<'a> Monitor<'a> {
+    // Build the monitor around the workspaces it starts with, showing the first of them
+    pub fn new(screen: &ScreenInfo, mut workspaces: Vec<Workspace<'a>>) -> Self {
+        for ws in workspaces.iter_mut() {
+            ws.set_geometry(screen.x_org, screen.y_org, screen.width, screen.height);
+        }
+        workspaces[0].visible = true;
+        Self {
+            x: screen.x_org,
+            y: screen.y_org,
+            width: screen.width,
+            height: screen.height,
+            workspaces,
+            current: 0,
+        }
+    }
+
+    pub fn workspace(&self) -> &Workspace<'a> {
+        &self.workspaces[self.current]
+    }
+
+    pub fn workspace_mut(&mut self) -> &mut Workspace<'a> {
+        &mut self.workspaces[self.current]
+    }
+
+    // Switch to the workspace with the given index, returning whether anything changed.
+    // Leaving a dynamic workspace with no windows destroys it.
+    pub fn view(&mut self, index: usize) -> bool {
+        if index >= self.workspaces.len() || index == self.current {
+            return false;
+        }
+        let previous = self.current;
+        let active = self.workspace().active;
+        let sticky = self.workspace_mut().take_sticky();
+        self.workspace_mut().hide();
+        self.current = index;
+        self.workspace_mut().show();
+        for client in sticky {
+            self.workspace_mut().adopt_client(client);
+        }
+        self.workspace_mut().set_active(active);
+        if self.workspaces[previous].dynamic && self.workspaces[previous].is_empty() {
+            self.remove(previous);
+        }
+        true
+    }
+
+    // Add an empty workspace with the given id and name, returning its index
+    pub fn create(&mut self, id: usize, name: String, config: &'a Config) -> usize {
+        let screen = ScreenInfo {x_org: self.x, y_org: self.y, width: self.width, height: self.height};
+        let mut ws = Workspace::new(id, name, &screen, config);
+        ws.dynamic = true;
+        self.workspaces.push(ws);
+        self.workspaces.len() - 1
+    }
+
+    // Drop a workspace that isn't being shown
+    pub fn remove(&mut self, index: usize) {
+        if index == self.current {
+            return;
+        }
+        self.workspaces.remove(index).destroy();
+        if self.current > index {
+            self.current -= 1;
+        }
+    }
+
+    // Take a workspace that isn't being shown away from the monitor
+    pub fn take(&mut self, index: usize) -> Option<Workspace<'a>> {
+        if index == self.current || index >= self.workspaces.len() {
+            return None;
+        }
+        let ws = self.workspaces.remove(index);
+        if self.current > index {
+            self.current -= 1;
+        }
+        Some(ws)
+    }
+
+    // Add a workspace from another monitor, fitting it to this one, returning its index
+    pub fn adopt(&mut self, mut ws: Workspace<'a>) -> usize {
+        ws.set_geometry(self.x, self.y, self.width, self.height);
+        self.workspaces.push(ws);
+        self.workspaces.len() - 1
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.workspaces.iter().position(|ws| ws.name == name)
+    }
+
+    pub fn find_id(&self, id: usize) -> Option<usize> {
+        self.workspaces.iter().position(|ws| ws.id == id)
+    }
+}
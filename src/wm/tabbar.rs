@@ -0,0 +1,139 @@
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{AtomEnum, ChangeGCAux, ConfigureWindowAux, ConnectionExt, CreateGCAux, CreateWindowAux, EventMask, Rectangle, WindowClass};
+
+use crate::config::Config;
+
+use super::layout::Rect;
+
+const FOCUSED_COLOR: u32 = 0x00bfff;
+const UNFOCUSED_COLOR: u32 = 0x8b8378;
+const FOCUSED_TEXT: u32 = 0x000000;
+const UNFOCUSED_TEXT: u32 = 0xffffff;
+
+// A strip of tabs showing the title of each window, drawn by the window manager itself
+pub struct TabBar {
+    pub window: u32, // Window the tabs are drawn into
+    gc: u32, // Graphics context used for drawing
+    rect: Rect, // Current position and size of the strip
+    ascent: i16, // Ascent of the font
+    descent: i16, // Descent of the font
+    char_width: i16, // Width of a single character of the font
+    mapped: bool, // Is the strip currently shown
+}
+
+impl TabBar {
+    pub fn new(config: &Config) -> Self {
+        let conn = &config.connection;
+        let screen = &conn.setup().roots[config.screen_num];
+
+        let window = conn.generate_id().expect("Unable to generate id");
+        conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            0, 0, 1, 1, 0,
+            WindowClass::INPUT_OUTPUT,
+            screen.root_visual,
+            &CreateWindowAux::new().override_redirect(1).background_pixel(UNFOCUSED_COLOR).event_mask(EventMask::EXPOSURE)
+        ).expect("Unable to create tab bar");
+
+        let font = conn.generate_id().expect("Unable to generate id");
+        conn.open_font(font, b"fixed").expect("Unable to open font");
+        let info = conn.query_font(font)
+            .expect("Unable to query font")
+            .reply().expect("Unable to query font");
+
+        let gc = conn.generate_id().expect("Unable to generate id");
+        conn.create_gc(gc, window, &CreateGCAux::new().font(font)).expect("Unable to create graphics context");
+        conn.close_font(font).expect("Unable to close font");
+
+        Self {
+            window,
+            gc,
+            rect: Rect::new(0, 0, 1, 1),
+            ascent: info.font_ascent,
+            descent: info.font_descent,
+            char_width: info.max_bounds.character_width.max(1),
+            mapped: false,
+        }
+    }
+
+    // Move the strip to the given rectangle and show it
+    pub fn show(&mut self, config: &Config, rect: Rect) {
+        if rect != self.rect {
+            config.connection.configure_window(self.window, &ConfigureWindowAux::new()
+                .x(rect.x).y(rect.y).width(rect.width.max(1) as u32).height(rect.height.max(1) as u32))
+                .expect("Unable to move tab bar");
+            self.rect = rect;
+        }
+        if !self.mapped {
+            config.connection.map_window(self.window).expect("Unable to map tab bar");
+            self.mapped = true;
+        }
+    }
+
+    pub fn hide(&mut self, config: &Config) {
+        if self.mapped {
+            config.connection.unmap_window(self.window).expect("Unable to unmap tab bar");
+            self.mapped = false;
+        }
+    }
+
+    pub fn destroy(&self, config: &Config) {
+        config.connection.free_gc(self.gc).expect("Unable to free graphics context");
+        config.connection.destroy_window(self.window).expect("Unable to destroy tab bar");
+    }
+
+    // Draw one tab per window, highlighting the focused one.
+    // Tabs sit side by side, or on top of each other when stacked.
+    pub fn draw(&self, config: &Config, windows: &[u32], focused: Option<u32>, stacked: bool) {
+        if !self.mapped || windows.is_empty() {
+            return;
+        }
+        let conn = &config.connection;
+        let strip = Rect::new(0, 0, self.rect.width, self.rect.height);
+        let tabs = if stacked {strip.rows(windows.len())} else {strip.columns(windows.len())};
+        for (win, tab) in windows.iter().zip(tabs) {
+            let (background, foreground) = if Some(*win) == focused {
+                (FOCUSED_COLOR, FOCUSED_TEXT)
+            } else {
+                (UNFOCUSED_COLOR, UNFOCUSED_TEXT)
+            };
+            conn.change_gc(self.gc, &ChangeGCAux::new().foreground(background)).expect("Unable to change graphics context");
+            conn.poly_fill_rectangle(self.window, self.gc, &[Rectangle {
+                x: tab.x as i16,
+                y: tab.y as i16,
+                width: tab.width.max(1) as u16,
+                height: tab.height.max(1) as u16,
+            }]).expect("Unable to draw tab");
+
+            // Cut the title down so it fits inside its tab
+            let max_chars = ((tab.width - self.char_width as i32) / self.char_width as i32).clamp(0, 255) as usize;
+            let title: Vec<u8> = window_title(config, *win).chars()
+                .map(|c| if c.is_ascii() && !c.is_ascii_control() {c as u8} else {b'?'})
+                .take(max_chars)
+                .collect();
+            let baseline = (tab.height + self.ascent as i32 - self.descent as i32) / 2;
+            conn.change_gc(self.gc, &ChangeGCAux::new().foreground(foreground).background(background)).expect("Unable to change graphics context");
+            conn.image_text8(self.window, self.gc, (tab.x + self.char_width as i32/2) as i16, baseline as i16, &title).expect("Unable to draw title");
+        }
+    }
+}
+
+// Get the title of a window, preferring the UTF-8 _NET_WM_NAME over WM_NAME
+pub fn window_title(config: &Config, win: u32) -> String {
+    let conn = &config.connection;
+    if let Ok(cookie) = conn.get_property(false, win, config.net_wm_name_atom, config.utf8_string_atom, 0, 256) {
+        if let Ok(reply) = cookie.reply() {
+            if !reply.value.is_empty() {
+                return String::from_utf8_lossy(&reply.value).into_owned();
+            }
+        }
+    }
+    if let Ok(cookie) = conn.get_property(false, win, AtomEnum::WM_NAME, AtomEnum::ANY, 0, 256) {
+        if let Ok(reply) = cookie.reply() {
+            return String::from_utf8_lossy(&reply.value).into_owned();
+        }
+    }
+    String::new()
+}
@@ -1,41 +1,88 @@
 
-use x11rb::{protocol::{xinerama::ScreenInfo, xproto::{ConnectionExt, ConfigureWindowAux, ChangeWindowAttributesAux, InputFocus, ClientMessageEvent, EventMask, GrabMode, ButtonIndex, ModMask, StackMode}}};
+use std::collections::HashMap;
+
+use x11rb::{protocol::{xinerama::ScreenInfo, xproto::{ConnectionExt, ConfigureWindowAux, ChangeWindowAttributesAux, InputFocus, ClientMessageEvent, EventMask, GrabMode, ButtonIndex, ModMask}}};
 
 use crate::config::Config;
 
+use super::client::{Client, Layer, SizeHints, ICONIC_STATE, NORMAL_STATE, window_geometry};
+use super::container::{Container, ContainerMode};
+use super::layout::{self, Layout, LayoutMessage, LayoutParams, Orientation, Rect};
+use super::tabbar::TabBar;
 
 
 pub struct Workspace<'a> {
     pub config: &'a Config, // Reference to config
+    pub id: usize, // Unique id of the workspace, which stays the same when it is renamed or moved
+    pub name: String, // Name used to refer to the workspace
+    pub dynamic: bool, // Was the workspace created at runtime, and so destroyed once left empty
     pub x: i16, // X coordinate of workspace
     pub y: i16, // Y coordinate of workspace
     pub width: u16, // Width of workspace
     pub height: u16, // Height of workspace
     pub master_width: u16,// Width of the master window
-    pub windows: Vec<u32>, // List of windows
+    pub nmaster: usize, // Number of windows in the master area
+    pub orientation: Orientation, // Side the master area sits on
+    pub layouts: Vec<Box<dyn Layout>>, // Layouts this workspace can switch between
+    pub layout: usize, // Index of the current layout
+    pub windows: Vec<u32>, // List of windows on the tags being viewed
+    pub hidden: Vec<u32>, // List of windows on other tags
+    pub minimized: Vec<u32>, // List of iconified windows, most recently minimized last
+    pub view: u32, // Bitmask of the tags being viewed
+    pub clients: HashMap<u32, Client>, // State of each window in the list
     pub focused: usize, // Focused Window
     pub active: bool, // Is the workspace currently focused
+    pub visible: bool, // Is the workspace currently shown on its monitor
     pub is_full: bool, // Is the workspace full screen
+    pub tab_bar: Option<TabBar>, // Strip of tabs, created the first time a layout asks for one
+    pub containers: Vec<Container>, // Groups of windows sharing a tile
+    pub tiles: HashMap<u32, Rect>, // Where the last tiling put each tiled window on top of its tile
 }
 
 impl<'a> Workspace<'a> {
-    pub fn new(screen: &ScreenInfo, config: &'a Config) -> Self {
+    pub fn new(id: usize, name: String, screen: &ScreenInfo, config: &'a Config) -> Self {
         Self {
             config,
+            id,
+            name,
+            dynamic: false,
             x: screen.x_org,
             y: screen.y_org,
             width: screen.width,
             height: screen.height,
             master_width: screen.width/2,
+            nmaster: config.nmaster,
+            orientation: config.orientation,
+            layouts: layout::defaults(),
+            layout: 0,
             windows: Vec::new(),
+            hidden: Vec::new(),
+            minimized: Vec::new(),
+            view: 1,
+            clients: HashMap::new(),
             focused: 0,
             active: false,
-            is_full: false
+            visible: false,
+            is_full: false,
+            tab_bar: None,
+            containers: Vec::new(),
+            tiles: HashMap::new()
         }
     }
 
     pub fn tile(&mut self) {
+        if !self.visible {
+            return;
+        }
+        let tiled = self.tiled_windows();
+        self.tiles.clear();
+        let strip = if self.is_full || tiled.is_empty() {None} else {self.layouts[self.layout].tab_strip(&self.layout_params(&tiled))};
+        self.update_tab_bar(strip);
+
         if self.is_full && !self.windows.is_empty() {
+            for container in self.containers.iter_mut() {
+                container.bar.hide(self.config);
+            }
             for win in self.windows.iter(){
                 self.config.connection.configure_window(*win, 
                     &ConfigureWindowAux::new().x(self.x as i32).y(self.y as i32)
@@ -45,66 +92,696 @@ impl<'a> Workspace<'a> {
             return;
         }
 
-        if self.windows.len() == 1 {
-            self.config.connection.configure_window(self.windows[0],
-                &ConfigureWindowAux::new().x(self.config.gap as i32 + self.x as i32).y(self.config.gap as i32 + self.y as i32)
-                .width((self.width-(self.config.gap*2))as u32).height((self.height-(self.config.gap*2)) as u32))
-                .expect("Unable to tile window");
+        // Groups with every window on other tags have nothing to show
+        for container in self.containers.iter_mut().filter(|container| container.windows.is_empty()) {
+            container.bar.hide(self.config);
+        }
+        self.sync_layout();
+        let rects = self.layouts[self.layout].arrange(&self.layout_params(&tiled));
+        for (win, rect) in tiled.iter().zip(rects) {
+            match self.container_of(*win) {
+                Some(i) => {
+                    let (bar, content) = self.containers[i].split(rect, self.config.border, self.config.tab_height);
+                    self.containers[i].bar.show(self.config, bar);
+                    for child in self.containers[i].windows.iter() {
+                        let placed = self.place_tiled(*child, content);
+                        if *child == self.containers[i].active_window() {
+                            self.tiles.insert(*child, placed);
+                        }
+                    }
+                    self.containers[i].draw(self.config);
+                },
+                None => {
+                    let placed = self.place_tiled(*win, rect);
+                    self.tiles.insert(*win, placed);
+                }
+            }
         }
-        else if self.windows.len() > 1 {
-            self.config.connection.configure_window(self.windows[0],
-                &ConfigureWindowAux::new().x(self.config.gap as i32 + self.x as i32).y(self.config.gap as i32 + self.y as i32)
-                .width(((self.master_width)-(self.config.gap*2)) as u32).height((self.height-(self.config.gap*2)) as u32))
-                .expect("Unable to tile window");
 
-            let stack_height = self.height / (self.windows.len() - 1) as u16;
-            for i in 1..self.windows.len(){
-                self.config.connection.configure_window(self.windows[i],
-                    &ConfigureWindowAux::new().x((self.master_width) as i32 + self.x as i32).y((stack_height*(i-1) as u16+self.config.gap) as i32 + self.y as i32)
-                    .width((self.width-self.master_width-self.config.gap) as u32).height((stack_height-(self.config.gap * if i == self.windows.len()-1 {2} else {1})) as u32))
-                    .expect("Unable to tile window");
+        for (win, client) in self.windows.iter().filter_map(|win| self.clients.get(win).map(|client| (win, client))) {
+            if client.fullscreen {
+                self.place_window(*win, Rect::new(self.x as i32, self.y as i32, self.width as i32, self.height as i32));
+            } else if client.floating {
+                self.place_window(*win, client.geometry);
             }
         }
     }
 
-    pub fn hide(&mut self) {
+    // The stacking layer a window is in
+    pub fn layer_of(&self, win: u32) -> Layer {
+        let client = &self.clients[&win];
+        match client.layer {
+            Some(layer) => layer,
+            None if self.is_full || client.fullscreen => Layer::Fullscreen,
+            None if client.floating => Layer::Floating,
+            None => Layer::Tiled
+        }
+    }
+
+    // The windows of the workspace with the layers they stack in, bottom to top. Within a layer
+    // dialogs go above the windows they belong to and the focused window goes on top, and the
+    // hidden windows of a group go below the shown one. Tab strips sit on top of the tiled layer.
+    pub fn stacking(&self) -> Vec<(Layer, u32)> {
+        if !self.visible {
+            return Vec::new();
+        }
+        let focused = self.windows.get(self.focused).copied();
+        let mut order = self.windows.clone();
+        order.sort_by_key(|win| {
+            let rank = if Some(*win) == focused {
+                2
+            } else if self.container_of(*win).is_some_and(|i| self.containers[i].active_window() != *win) {
+                0
+            } else {
+                1
+            };
+            (self.layer_of(*win), self.clients[win].transient_for.is_some(), rank)
+        });
+        let bars: Vec<u32> = self.tab_bar.iter().map(|bar| bar.window)
+            .chain(self.containers.iter().map(|container| container.bar.window))
+            .collect();
+        let split = order.partition_point(|win| self.layer_of(*win) <= Layer::Tiled);
+        order[..split].iter().map(|win| (self.layer_of(*win), *win))
+            .chain(bars.into_iter().map(|bar| (Layer::Tiled, bar)))
+            .chain(order[split..].iter().map(|win| (self.layer_of(*win), *win)))
+            .collect()
+    }
 
+    // A rectangle of the given size in the middle of the workspace
+    pub fn centered(&self, width: i32, height: i32) -> Rect {
+        Rect::new(self.x as i32 + (self.width as i32 - width)/2, self.y as i32 + (self.height as i32 - height)/2, width, height)
     }
 
-    pub fn show(&mut self) {
+    // Center a window of the given size over an area, keeping as much of it as fits on the monitor
+    pub fn centered_over(&self, area: Rect, width: i32, height: i32) -> Rect {
+        let (x, y) = (self.x as i32, self.y as i32);
+        Rect::new(
+            (area.x + (area.width - width)/2).min(x + self.width as i32 - width).max(x),
+            (area.y + (area.height - height)/2).min(y + self.height as i32 - height).max(y),
+            width,
+            height,
+        )
+    }
+
+    fn place_window(&self, win: u32, rect: Rect) {
+        self.config.connection.configure_window(win,
+            &ConfigureWindowAux::new().x(rect.x).y(rect.y)
+            .width(rect.width.max(1) as u32).height(rect.height.max(1) as u32))
+            .expect("Unable to tile window");
+    }
+
+    // Place a window in its tile, fitting it to its size hints if its rule asks for that, and
+    // return where it went
+    fn place_tiled(&self, win: u32, rect: Rect) -> Rect {
+        let Some(client) = self.clients.get(&win).filter(|client| client.honor_hints) else {
+            self.place_window(win, rect);
+            return rect;
+        };
+        // A window whose minimum is larger than its tile overflows from the tile's top left corner
+        let (width, height) = client.hints.constrain(rect.width, rect.height);
+        let placed = Rect::new(rect.x + ((rect.width - width)/2).max(0), rect.y + ((rect.height - height)/2).max(0), width, height);
+        self.place_window(win, placed);
+        placed
+    }
+
+    fn layout_params<'b>(&'b self, windows: &'b [u32]) -> LayoutParams<'b> {
+        LayoutParams {
+            area: Rect::new(self.x as i32, self.y as i32, self.width as i32, self.height as i32),
+            gap: self.config.gap,
+            border: self.config.border,
+            tab_height: self.config.tab_height,
+            master_ratio: self.master_width as f32 / self.width as f32,
+            nmaster: self.nmaster,
+            orientation: self.orientation,
+            windows,
+            weights: windows.iter().map(|win| self.clients.get(win).map_or(1.0, |client| client.weight)).collect(),
+        }
+    }
+
+    // Windows handed to the layout: every window that isn't floating or inside a container,
+    // and the first window of each container. Windows without a client are skipped.
+    fn tiled_windows(&self) -> Vec<u32> {
+        self.windows.iter().copied()
+            .filter(|win| self.clients.get(win).is_some_and(|client| !client.floating && !client.fullscreen) && self.tiled_window(*win) == *win)
+            .collect()
+    }
+
+    // The window standing in for the given one when tiling
+    fn tiled_window(&self, win: u32) -> u32 {
+        match self.container_of(win) {
+            Some(i) => self.containers[i].windows[0],
+            None => win
+        }
+    }
+
+    pub fn container_of(&self, win: u32) -> Option<usize> {
+        self.containers.iter().position(|container| container.windows.contains(&win))
+    }
+
+    // Show the tab strip at the given rectangle, or hide it if there is none
+    fn update_tab_bar(&mut self, strip: Option<Rect>) {
+        match strip {
+            Some(rect) => {
+                let bar = self.tab_bar.get_or_insert_with(|| TabBar::new(self.config));
+                bar.show(self.config, rect);
+            },
+            None => {
+                if let Some(bar) = &mut self.tab_bar {
+                    bar.hide(self.config);
+                }
+            }
+        }
+        self.draw_tabs();
+    }
+
+    pub fn draw_tabs(&self) {
+        if let Some(bar) = &self.tab_bar {
+            let focused = self.windows.get(self.focused).map(|win| self.tiled_window(*win));
+            bar.draw(self.config, &self.tiled_windows(), focused, false);
+        }
+        for container in self.containers.iter() {
+            container.draw(self.config);
+        }
+    }
+
+    // Is the given window one of the tab strips or title bars drawn for this workspace
+    pub fn has_bar(&self, window: u32) -> bool {
+        self.tab_bar.as_ref().map(|bar| bar.window) == Some(window)
+            || self.containers.iter().any(|container| container.bar.window == window)
+    }
+
+    pub fn layout_message(&mut self, message: LayoutMessage) {
+        let focused = self.windows.get(self.focused).copied();
+        if self.layouts[self.layout].message(&message, focused) {
+            self.tile();
+        }
+    }
+
+    pub fn rotate_layout(&mut self) {
+        self.orientation = self.orientation.next();
+        self.tile();
+    }
+
+    pub fn cycle_layout(&mut self, direction: bool) {
+        self.layout = match direction {
+            true => (self.layout + 1) % self.layouts.len(),
+            false => (self.layout + self.layouts.len() - 1) % self.layouts.len()
+        };
+        self.tile();
+    }
+
+    // Does the workspace hold no windows at all, including those on other tags
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    // Take every window out of the workspace, in order, restoring any that were minimized
+    pub fn take_clients(&mut self) -> Vec<Client> {
+        for win in self.minimized.iter() {
+            self.clients[win].set_state(self.config, NORMAL_STATE);
+        }
+        let windows: Vec<u32> = self.windows.iter().chain(self.hidden.iter()).chain(self.minimized.iter()).copied().collect();
+        windows.into_iter().filter_map(|win| self.remove_window(win)).collect()
+    }
+
+    // Free the windows the workspace drew itself
+    pub fn destroy(&mut self) {
+        if let Some(bar) = self.tab_bar.take() {
+            bar.destroy(self.config);
+        }
+        for container in self.containers.drain(..) {
+            container.bar.destroy(self.config);
+        }
+    }
+
+    // Unmap every window so another workspace can take over the monitor
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.active = false;
+        for client in self.clients.values_mut() {
+            client.hide(self.config);
+        }
+        if let Some(bar) = &mut self.tab_bar {
+            bar.hide(self.config);
+        }
+        for container in self.containers.iter_mut() {
+            container.bar.hide(self.config);
+        }
+    }
 
+    // Map every window again and put them back in place
+    pub fn show(&mut self) {
+        self.visible = true;
+        for win in self.windows.iter() {
+            if let Some(client) = self.clients.get_mut(win) {
+                client.show(self.config);
+            }
+        }
+        self.tile();
     }
 
     pub fn add_window(&mut self, window: u32) {
-        self.config.connection.configure_window(window, &ConfigureWindowAux::new().border_width(if self.is_full {0} else {3})).expect("Unable to set border width");
+        self.add_client(Client::new(window));
+    }
+
+    pub fn add_client(&mut self, mut client: Client) {
+        let window = client.window;
+        if client.tags & self.view == 0 {
+            client.tags = self.view;
+        }
+        if self.visible {
+            client.show(self.config);
+        } else {
+            client.hide(self.config);
+        }
+        self.config.connection.configure_window(window, &ConfigureWindowAux::new().border_width(self.border_of(&client))).expect("Unable to set border width");
+        self.clients.insert(window, client);
         self.windows.insert(0, window);
         self.set_focus(0);
         self.tile();
     }
 
-    pub fn remove_focused(&mut self) -> Option<u32> {
+    // Add a window at the end of the list without moving focus to it
+    pub fn adopt_client(&mut self, mut client: Client) {
+        let window = client.window;
+        if client.tags & self.view == 0 {
+            client.tags = self.view;
+        }
+        if self.visible {
+            client.show(self.config);
+        }
+        self.config.connection.configure_window(window, &ConfigureWindowAux::new().border_width(self.border_of(&client))).expect("Unable to set border width");
+        self.clients.insert(window, client);
+        self.windows.push(window);
+        self.tile();
+        self.set_focus(self.focused);
+    }
+
+    // The dialogs belonging to the given window
+    fn transients(&self, parent: u32) -> Vec<u32> {
+        self.windows.iter().chain(self.hidden.iter()).copied()
+            .filter(|win| self.clients.get(win).is_some_and(|client| client.transient_for == Some(parent)))
+            .collect()
+    }
+
+    // Take out the dialogs belonging to the given window
+    pub fn take_transients(&mut self, parent: u32) -> Vec<Client> {
+        self.transients(parent).into_iter().filter_map(|win| self.remove_window(win)).collect()
+    }
+
+    // Take out the focused window along with its dialogs, the window coming first
+    pub fn take_focused(&mut self) -> Vec<Client> {
+        let Some(client) = self.remove_focused() else {
+            return Vec::new();
+        };
+        let dialogs = self.take_transients(client.window);
+        std::iter::once(client).chain(dialogs).collect()
+    }
+
+    // Take out the windows that stay on screen whichever workspace is shown
+    pub fn take_sticky(&mut self) -> Vec<Client> {
+        let sticky: Vec<u32> = self.windows.iter().copied().filter(|win| self.clients[win].sticky).collect();
+        sticky.into_iter().filter_map(|win| self.remove_window(win)).collect()
+    }
+
+    // Keep the focused window above the tiled and floating windows, or put it back in the layer it
+    // asked for
+    pub fn toggle_always_on_top(&mut self) {
+        if let Some(client) = self.windows.get(self.focused).and_then(|win| self.clients.get_mut(win)) {
+            client.layer = if client.layer == Some(Layer::Above) {client.requested_layer.filter(|layer| *layer != Layer::Above)} else {Some(Layer::Above)};
+            client.set_layer_state(self.config);
+        }
+    }
+
+    // Make the focused window and its dialogs sticky, or stop them being sticky
+    pub fn toggle_sticky(&mut self) {
+        let Some(&win) = self.windows.get(self.focused) else {
+            return;
+        };
+        let sticky = !self.clients.get(&win).is_some_and(|client| client.sticky);
+        for win in std::iter::once(win).chain(self.transients(win)) {
+            if let Some(client) = self.clients.get_mut(&win) {
+                client.sticky = sticky;
+            }
+        }
+    }
+
+    pub fn remove_focused(&mut self) -> Option<Client> {
         if self.windows.is_empty() {
             return None;
         }
-        return self.remove_window(self.windows[self.focused]);
+        self.remove_window(self.windows[self.focused])
     }
 
-    pub fn remove_window(&mut self, to_remove: u32) -> Option<u32> {
-        let mut removed = None;
+    pub fn remove_window(&mut self, to_remove: u32) -> Option<Client> {
+        let removed = self.clients.remove(&to_remove);
+        self.leave_container(to_remove);
+        self.hidden.retain(|window| *window != to_remove);
+        self.minimized.retain(|window| *window != to_remove);
         self.windows.retain(|window|{
             if *window != to_remove{
                 return true;
             }
-            removed = Some(*window);
             //self.config.connection.change_save_set(SetMode::DELETE, *window).expect("Unable to change save state");
             //self.config.connection.reparent_window(*window, self.config.connection.setup().roots[self.config.screen_num].root, 0,0).expect("Unable to reparent window");
-            return false;
+            false
         });
-        if let Some(_) = removed {
+        if removed.is_some() {
             self.set_focus(self.focused);
             self.tile();
         }
-        return removed;
+        removed
+    }
+
+    // Float the focused window where it is, or put it back into the layout
+    pub fn toggle_float(&mut self) {
+        let Some(win) = self.windows.get(self.focused).copied() else {
+            return;
+        };
+        if self.clients[&win].floating {
+            if let Some(client) = self.clients.get_mut(&win) {
+                client.floating = false;
+            }
+            self.tile();
+            self.set_focus(self.focused);
+        } else {
+            let geometry = window_geometry(self.config, win).unwrap_or_else(|| self.centered(self.width as i32/2, self.height as i32/2));
+            self.float_window(win, geometry);
+        }
+    }
+
+    // Take a window out of the layout and give it its own geometry
+    pub fn float_window(&mut self, win: u32, geometry: Rect) {
+        self.leave_container(win);
+        if let Some(client) = self.clients.get_mut(&win) {
+            client.floating = true;
+            client.geometry = geometry;
+        }
+        self.tile();
+        self.set_focus(self.focused);
+    }
+
+    // Update the size constraints of a window, floating it if it can only be one size
+    pub fn set_size_hints(&mut self, win: u32, hints: SizeHints) {
+        let Some(client) = self.clients.get_mut(&win) else {
+            return;
+        };
+        client.hints = hints;
+        if hints.is_fixed() && !client.floating {
+            let (width, height) = hints.min.unwrap_or((1, 1));
+            let geometry = self.centered(width, height);
+            self.float_window(win, geometry);
+        } else {
+            self.tile();
+        }
+    }
+
+    // Move a floating window to the given geometry
+    pub fn move_floating(&mut self, win: u32, geometry: Rect) {
+        if let Some(client) = self.clients.get_mut(&win).filter(|client| client.floating) {
+            client.geometry = geometry;
+            self.place_window(win, geometry);
+        }
+    }
+
+    // The focused window and its geometry, if it is floating
+    fn focused_floating(&self) -> Option<(u32, Rect)> {
+        self.windows.get(self.focused)
+            .filter(|win| self.clients[*win].floating)
+            .map(|win| (*win, self.clients[win].geometry))
+    }
+
+    pub fn move_focused_float(&mut self, dx: i32, dy: i32) {
+        if let Some((win, rect)) = self.focused_floating() {
+            let geometry = self.snap_move(win, Rect::new(rect.x + dx, rect.y + dy, rect.width, rect.height), (dx, dy), true);
+            self.move_floating(win, geometry);
+        }
+    }
+
+    // Grow or shrink the focused floating window from its bottom right corner
+    pub fn resize_focused_float(&mut self, dw: i32, dh: i32) {
+        if let Some((win, rect)) = self.focused_floating() {
+            let geometry = self.snap_resize(win, Rect::new(rect.x, rect.y, rect.width + dw, rect.height + dh), true, true, (dw, dh), true);
+            let (width, height) = self.clients[&win].hints.constrain(geometry.width, geometry.height);
+            self.move_floating(win, Rect::new(geometry.x, geometry.y, width, height));
+        }
+    }
+
+    pub fn center_focused_float(&mut self) {
+        if let Some((win, rect)) = self.focused_floating() {
+            let geometry = self.centered(rect.width, rect.height);
+            self.move_floating(win, geometry);
+        }
+    }
+
+    // Move the focused floating window flush against an edge of the monitor
+    pub fn snap_focused_float(&mut self, edge: Orientation) {
+        if let Some((win, rect)) = self.focused_floating() {
+            let border = 2 * self.config.border as i32;
+            let (x, y) = match edge {
+                Orientation::Left => (self.x as i32, rect.y),
+                Orientation::Top => (rect.x, self.y as i32),
+                Orientation::Right => (self.x as i32 + self.width as i32 - rect.width - border, rect.y),
+                Orientation::Bottom => (rect.x, self.y as i32 + self.height as i32 - rect.height - border)
+            };
+            self.move_floating(win, Rect::new(x, y, rect.width, rect.height));
+        }
+    }
+
+    // Pull a moved floating window onto monitor and window edges within the snap threshold. A
+    // keyboard step, where the direction is the whole step, also stops at the edges it crosses.
+    pub fn snap_move(&self, win: u32, rect: Rect, direction: (i32, i32), stepped: bool) -> Rect {
+        let border = 2 * self.config.border as i32;
+        let (xs, ys) = self.snap_edges(win);
+        let snap = |position: i32, edges: &[i32], direction: i32| snap_offset(position, edges, direction, if stepped {direction} else {0}, self.config.snap_threshold as i32);
+        let dx = pick_snap([snap(rect.x, &xs, direction.0), snap(rect.x + rect.width + border, &xs, direction.0)], direction.0);
+        let dy = pick_snap([snap(rect.y, &ys, direction.1), snap(rect.y + rect.height + border, &ys, direction.1)], direction.1);
+        Rect::new(rect.x + dx, rect.y + dy, rect.width, rect.height)
+    }
+
+    // Pull the dragged edges of a resized floating window onto monitor and window edges within the
+    // snap threshold, stopping a keyboard step at the edges it crosses
+    pub fn snap_resize(&self, win: u32, rect: Rect, right: bool, bottom: bool, direction: (i32, i32), stepped: bool) -> Rect {
+        let border = 2 * self.config.border as i32;
+        let (xs, ys) = self.snap_edges(win);
+        let snap = |position: i32, edges: &[i32], direction: i32| snap_offset(position, edges, direction, if stepped {direction} else {0}, self.config.snap_threshold as i32);
+        let mut rect = rect;
+        if right {
+            rect.width += snap(rect.x + rect.width + border, &xs, direction.0).unwrap_or(0);
+        } else if let Some(dx) = snap(rect.x, &xs, direction.0) {
+            rect.x += dx;
+            rect.width -= dx;
+        }
+        if bottom {
+            rect.height += snap(rect.y + rect.height + border, &ys, direction.1).unwrap_or(0);
+        } else if let Some(dy) = snap(rect.y, &ys, direction.1) {
+            rect.y += dy;
+            rect.height -= dy;
+        }
+        Rect::new(rect.x, rect.y, rect.width.max(1), rect.height.max(1))
+    }
+
+    // Edges of the monitor and of the other windows on screen, across and down, that floating
+    // windows snap to
+    fn snap_edges(&self, win: u32) -> (Vec<i32>, Vec<i32>) {
+        let border = 2 * self.config.border as i32;
+        let mut xs = vec![self.x as i32, self.x as i32 + self.width as i32];
+        let mut ys = vec![self.y as i32, self.y as i32 + self.height as i32];
+        let others = self.windows.iter()
+            .filter(|other| **other != win)
+            .filter_map(|other| self.clients.get(other).filter(|client| client.mapped).map(|client| (other, client)))
+            .filter_map(|(other, client)| if client.floating {Some(client.geometry)} else {self.tiles.get(other).copied()});
+        for geometry in others {
+            xs.extend([geometry.x, geometry.x + geometry.width + border]);
+            ys.extend([geometry.y, geometry.y + geometry.height + border]);
+        }
+        (xs, ys)
+    }
+
+    // Iconify the focused window, taking it out of the layout until it is restored
+    pub fn minimize_focused(&mut self) {
+        // Dialogs go first so that restoring the last window brings back the one they belong to
+        let mut clients = self.take_focused();
+        clients.rotate_left(1);
+        for mut client in clients {
+            client.hide(self.config);
+            client.set_state(self.config, ICONIC_STATE);
+            self.minimized.push(client.window);
+            self.clients.insert(client.window, client);
+        }
+    }
+
+    // Bring back the most recently minimized window, returning false if there was none
+    pub fn restore_last(&mut self) -> bool {
+        match self.minimized.last().copied() {
+            Some(win) => self.restore(win),
+            None => false
+        }
+    }
+
+    // Bring back a minimized window, returning false if it isn't minimized here
+    pub fn restore(&mut self, win: u32) -> bool {
+        let Some(pos) = self.minimized.iter().position(|minimized| *minimized == win) else {
+            return false;
+        };
+        self.minimized.remove(pos);
+        let Some(client) = self.clients.remove(&win) else {
+            return false;
+        };
+        client.set_state(self.config, NORMAL_STATE);
+        self.add_client(client);
+        let dialogs: Vec<u32> = self.minimized.iter().copied()
+            .filter(|dialog| self.clients.get(dialog).is_some_and(|client| client.transient_for == Some(win)))
+            .collect();
+        for dialog in dialogs {
+            self.restore(dialog);
+        }
+        true
+    }
+
+    // Show only the tags in the given bitmask
+    pub fn set_view(&mut self, view: u32) {
+        if view == 0 || view == self.view {
+            return;
+        }
+        self.view = view;
+        self.apply_tags();
+    }
+
+    // Give the focused window the tags in the given bitmask
+    pub fn set_focused_tags(&mut self, tags: u32) {
+        if tags == 0 || self.windows.is_empty() {
+            return;
+        }
+        let win = self.windows[self.focused];
+        for win in std::iter::once(win).chain(self.transients(win)) {
+            if let Some(client) = self.clients.get_mut(&win) {
+                client.tags = tags;
+            }
+        }
+        self.apply_tags();
+    }
+
+    pub fn focused_tags(&self) -> u32 {
+        self.windows.get(self.focused).map_or(0, |win| self.clients[win].tags)
+    }
+
+    // Move windows between the viewed and hidden lists after the view or a window's tags changed
+    fn apply_tags(&mut self) {
+        let focused = self.windows.get(self.focused).copied();
+        let (shown, hidden): (Vec<u32>, Vec<u32>) = self.windows.iter().chain(self.hidden.iter())
+            .partition(|win| self.clients[*win].sticky || self.clients[*win].tags & self.view != 0);
+        for container in self.containers.iter_mut() {
+            container.apply_view(&shown);
+        }
+        for win in hidden.iter() {
+            if let Some(client) = self.clients.get_mut(win) {
+                client.hide(self.config);
+            }
+        }
+        if self.visible {
+            for win in shown.iter() {
+                if let Some(client) = self.clients.get_mut(win) {
+                    client.show(self.config);
+                }
+            }
+        }
+        self.windows = shown;
+        self.hidden = hidden;
+        let index = focused.and_then(|win| self.windows.iter().position(|shown| *shown == win)).unwrap_or(0);
+        self.tile();
+        self.set_focus(index);
+    }
+
+    // Group the focused window with its neighbour, or change the mode of the group it is already in
+    pub fn group_focused(&mut self, mode: ContainerMode) {
+        if self.windows.is_empty() {
+            return;
+        }
+        let win = self.windows[self.focused];
+        if let Some(i) = self.container_of(win) {
+            self.containers[i].mode = mode;
+            self.tile();
+            return;
+        }
+
+        let tiled = self.tiled_windows();
+        let Some(pos) = tiled.iter().position(|tile| *tile == win) else {
+            return;
+        };
+        if tiled.len() < 2 {
+            return;
+        }
+        let neighbour = if pos + 1 < tiled.len() {tiled[pos + 1]} else {tiled[pos - 1]};
+        match self.container_of(neighbour) {
+            Some(i) => {
+                let container = &mut self.containers[i];
+                container.windows.push(win);
+                container.active = container.windows.len() - 1;
+                container.mode = mode;
+            },
+            None => self.containers.push(Container::new(self.config, vec![neighbour, win], 1, mode))
+        }
+        self.tile();
+        self.set_focus(self.focused);
+    }
+
+    // Take the focused window out of its group and give it a tile of its own
+    pub fn ungroup_focused(&mut self) {
+        if self.windows.is_empty() || self.container_of(self.windows[self.focused]).is_none() {
+            return;
+        }
+        self.leave_container(self.windows[self.focused]);
+        self.tile();
+        self.set_focus(self.focused);
+    }
+
+    // Focus the next or previous window in the focused window's group
+    pub fn cycle_group(&mut self, direction: bool) {
+        if self.windows.is_empty() {
+            return;
+        }
+        let win = self.windows[self.focused];
+        let Some(i) = self.container_of(win) else {
+            return;
+        };
+        let container = &self.containers[i];
+        let len = container.windows.len();
+        let pos = container.windows.iter().position(|child| *child == win).unwrap_or(0);
+        let next = container.windows[match direction {
+            true => (pos + 1) % len,
+            false => (pos + len - 1) % len
+        }];
+        if let Some(index) = self.windows.iter().position(|child| *child == next) {
+            self.set_focus(index);
+            self.tile();
+        }
+    }
+
+    // Remove a window from its group, breaking the group up once a single window is left
+    fn leave_container(&mut self, win: u32) {
+        let Some(i) = self.containers.iter().position(|container| container.contains(win)) else {
+            return;
+        };
+        let container = &mut self.containers[i];
+        container.windows.retain(|child| *child != win);
+        container.hidden.retain(|child| *child != win);
+        if container.windows.len() + container.hidden.len() <= 1 {
+            self.containers.remove(i).bar.destroy(self.config);
+        } else {
+            container.active = container.active.min(container.windows.len().saturating_sub(1));
+        }
+    }
+
+    // Show the focused window if it is part of a group
+    fn activate_focused(&mut self) {
+        let Some(win) = self.windows.get(self.focused).copied() else {
+            return;
+        };
+        if let Some(i) = self.container_of(win) {
+            let container = &mut self.containers[i];
+            container.active = container.windows.iter().position(|child| *child == win).unwrap_or(0);
+        }
     }
 
     pub fn set_active(&mut self, state: bool) {
@@ -112,17 +789,26 @@ impl<'a> Workspace<'a> {
         self.set_focus(self.focused);
     }
 
+    // Bring the layout up to date with the windows and the focused one, so a layout that places new
+    // windows next to the focused one sees focus move before the new window is tiled
+    fn sync_layout(&mut self) {
+        let tiled = self.tiled_windows();
+        let focused = self.windows.get(self.focused).map(|win| self.tiled_window(*win));
+        self.layouts[self.layout].sync(&tiled, focused);
+    }
+
     pub fn set_focus(&mut self, index: usize) {
         if self.windows.is_empty(){
             self.focused = 0;
             return;
         }
         self.focused = index % self.windows.len();
+        self.activate_focused();
+        self.sync_layout();
         for i in 0..self.windows.len() {
             if i == self.focused && self.active {
                 self.config.connection.ungrab_button(ButtonIndex::ANY, self.windows[i], ModMask::ANY).expect("Unable to ungrab button");
                 self.config.connection.change_window_attributes(self.windows[i], &ChangeWindowAttributesAux::new().border_pixel(0x00bfff)).expect("Unable to set attributes");
-                self.config.connection.configure_window(self.windows[i], &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE)).expect("Unable to raise window");
                 self.config.connection.set_input_focus(InputFocus::PARENT, self.windows[i], x11rb::CURRENT_TIME).expect("Unable to focus window");
             }
             else {
@@ -140,6 +826,28 @@ impl<'a> Workspace<'a> {
                 self.config.connection.change_window_attributes(self.windows[i], &ChangeWindowAttributesAux::new().border_pixel(0x8b8378)).expect("Unable to set attributes");
             }
         }
+        self.draw_tabs();
+    }
+
+    // Move the workspace to a monitor with the given geometry, keeping the master share of the width
+    // and where floating windows sit relative to the workspace
+    pub fn set_geometry(&mut self, x: i16, y: i16, width: u16, height: u16){
+        let (old_x, old_y, old_width, old_height) = (self.x as i32, self.y as i32, self.width.max(1) as i32, self.height.max(1) as i32);
+        self.master_width = (self.master_width as i32 * width as i32 / old_width) as u16;
+        for client in self.clients.values_mut() {
+            let geometry = client.geometry;
+            client.geometry = Rect::new(
+                x as i32 + (geometry.x - old_x) * width as i32 / old_width,
+                y as i32 + (geometry.y - old_y) * height as i32 / old_height,
+                geometry.width,
+                geometry.height,
+            );
+        }
+        self.x = x;
+        self.y = y;
+        self.width = width;
+        self.height = height;
+        self.tile();
     }
 
     pub fn set_master_width(&mut self, width: u16){
@@ -156,6 +864,21 @@ impl<'a> Workspace<'a> {
         self.tile();
     }
 
+    pub fn change_nmaster(&mut self, amount: i32){
+        self.nmaster = (self.nmaster as i32 + amount).max(0) as usize;
+        self.tile();
+    }
+
+    pub fn change_stack_size(&mut self, amount: f32){
+        if self.windows.is_empty() {
+            return;
+        }
+        if let Some(client) = self.clients.get_mut(&self.windows[self.focused]) {
+            client.weight = (client.weight + amount).clamp(0.1, 10.0);
+        }
+        self.tile();
+    }
+
     pub fn close_focused(&mut self){
         if self.windows.is_empty() {
             return;
@@ -185,14 +908,25 @@ impl<'a> Workspace<'a> {
         self.tile();
     }
 
+    // Turn off the fullscreen of a window that brought it from another workspace, or else switch
+    // the whole workspace in or out of fullscreen
     pub fn toggle_full(&mut self){
-        self.is_full = !self.is_full;
-        for win in self.windows.iter() {
-            self.config.connection.configure_window(*win, &ConfigureWindowAux::new().border_width(if self.is_full {0} else {3})).expect("Unable to set border width");
+        if let Some(client) = self.windows.get(self.focused).and_then(|win| self.clients.get_mut(win)).filter(|client| client.fullscreen) {
+            client.fullscreen = false;
+        } else {
+            self.is_full = !self.is_full;
+        }
+        for (win, client) in self.windows.iter().filter_map(|win| self.clients.get(win).map(|client| (win, client))) {
+            self.config.connection.configure_window(*win, &ConfigureWindowAux::new().border_width(self.border_of(client))).expect("Unable to set border width");
         }
         self.tile();
     }
 
+    // Width of the border a window gets, which fullscreen windows go without
+    fn border_of(&self, client: &Client) -> u32 {
+        if self.is_full || client.fullscreen {0} else {self.config.border as u32}
+    }
+
     pub fn focus_window_id(&mut self, win: u32) -> bool{
         if self.windows.is_empty(){
             self.focused = 0;
@@ -202,10 +936,10 @@ impl<'a> Workspace<'a> {
         for i in 0..self.windows.len() {
             if self.windows[i] == win {
                 self.focused = i;
+                self.activate_focused();
                 self.active = true;
                 self.config.connection.ungrab_button(ButtonIndex::ANY, self.windows[i], ModMask::ANY).expect("Unable to ungrab button");
                 self.config.connection.change_window_attributes(self.windows[i], &ChangeWindowAttributesAux::new().border_pixel(0x00bfff)).expect("Unable to set attributes");
-                self.config.connection.configure_window(self.windows[i], &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE)).expect("Unable to raise window");
                 self.config.connection.set_input_focus(InputFocus::PARENT, self.windows[i], x11rb::CURRENT_TIME).expect("Unable to focus window");
                 ret = true;
             }
@@ -224,6 +958,81 @@ impl<'a> Workspace<'a> {
                 self.config.connection.change_window_attributes(self.windows[i], &ChangeWindowAttributesAux::new().border_pixel(0x8b8378)).expect("Unable to set attributes");
             }
         }
-        return ret;
+        self.sync_layout();
+        self.draw_tabs();
+        ret
     }
-}
\ No newline at end of file
+}
+
+// How far an edge has to move to meet one of the given edges. The first edge crossed during a
+// keyboard step of the given length stops it there. Otherwise the nearest edge within the threshold
+// pulls the window on, going only the way it is already moving so a drag can pull it free again.
+fn snap_offset(position: i32, edges: &[i32], direction: i32, step: i32, threshold: i32) -> Option<i32> {
+    if direction == 0 {
+        return None;
+    }
+    let sign = direction.signum();
+    let ahead = || edges.iter().map(|edge| (edge - position) * sign);
+    ahead().filter(|offset| *offset < 0 && *offset > -step.abs()).min()
+        .or_else(|| ahead().filter(|offset| *offset >= 0 && *offset <= threshold).min())
+        .map(|offset| offset * sign)
+}
+
+// Choose between the snaps of a window's two edges: a crossed edge wins, the one crossed first, and
+// otherwise the nearest pull
+fn pick_snap(offsets: [Option<i32>; 2], direction: i32) -> i32 {
+    offsets.into_iter().flatten()
+        .min_by_key(|offset| if offset * direction < 0 {(0, -offset.abs())} else {(1, offset.abs())})
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EDGES: [i32; 2] = [0, 1920];
+
+    #[test]
+    fn drags_snap_ahead_within_the_threshold() {
+        assert_eq!(snap_offset(1910, &EDGES, 1, 0, 16), Some(10));
+        assert_eq!(snap_offset(10, &EDGES, -1, 0, 16), Some(-10));
+        assert_eq!(snap_offset(1900, &EDGES, 1, 0, 16), None);
+    }
+
+    #[test]
+    fn drags_pull_free_of_an_edge_behind_them() {
+        assert_eq!(snap_offset(1925, &EDGES, 1, 0, 16), None);
+        assert_eq!(snap_offset(-5, &EDGES, -1, 0, 16), None);
+    }
+
+    #[test]
+    fn no_movement_no_snap() {
+        assert_eq!(snap_offset(5, &EDGES, 0, 0, 16), None);
+    }
+
+    #[test]
+    fn keyboard_steps_stop_at_crossed_edges() {
+        // A window 10 px from the left edge moved 20 px left ends up 10 px past it
+        assert_eq!(snap_offset(-10, &EDGES, -20, -20, 16), Some(10));
+        assert_eq!(snap_offset(1935, &EDGES, 20, 20, 16), Some(-15));
+    }
+
+    #[test]
+    fn keyboard_steps_leave_the_edge_they_start_on() {
+        assert_eq!(snap_offset(20, &EDGES, 20, 20, 16), None);
+        assert_eq!(snap_offset(-20, &EDGES, -20, -20, 16), None);
+    }
+
+    #[test]
+    fn keyboard_steps_stop_at_the_first_edge_crossed() {
+        assert_eq!(snap_offset(30, &[0, 10, 20], 25, 25, 16), Some(-20));
+    }
+
+    #[test]
+    fn crossed_edges_win_over_pulls() {
+        assert_eq!(pick_snap([Some(5), Some(-12)], 20), -12);
+        assert_eq!(pick_snap([Some(-3), Some(-12)], 20), -12);
+        assert_eq!(pick_snap([Some(5), Some(3)], 20), 3);
+        assert_eq!(pick_snap([None, None], 20), 0);
+    }
+}